@@ -0,0 +1,100 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use bech32::primitives::decode::CheckedHrpstring;
+use bech32::Bech32;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::MultiSendError;
+
+// The longest address accepted, same as `MaxAddrLen` in cosmos sdk.
+const MAX_ADDRESS_LENGTH: usize = 255;
+
+// Address is an account address. Any non-empty string of visible ASCII characters (no whitespace) is a valid
+// address, so test and module accounts like "issuer_account_A" can be used. Real chain addresses can also
+// be checked for their bech32 prefix and checksum with `Address::from_bech32`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address(String);
+
+impl Address {
+    // from_bech32 parses a bech32 address like "cosmos1..." and verifies its checksum and, if given, its
+    // prefix (the human-readable part before the last "1").
+    pub fn from_bech32(address: &str, prefix: Option<&str>) -> Result<Self, MultiSendError> {
+        let invalid = || MultiSendError::InvalidAddress {
+            address: address.to_string(),
+        };
+        let checked = CheckedHrpstring::new::<Bech32>(address).map_err(|_| invalid())?;
+        if prefix.is_some_and(|prefix| !checked.hrp().as_str().eq_ignore_ascii_case(prefix)) {
+            return Err(invalid());
+        }
+        address.parse()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Address {
+    type Err = MultiSendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty()
+            || s.len() > MAX_ADDRESS_LENGTH
+            || !s.bytes().all(|byte| byte.is_ascii_graphic())
+        {
+            return Err(MultiSendError::InvalidAddress {
+                address: s.to_string(),
+            });
+        }
+        Ok(Address(s.to_string()))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// A `&Address` can be passed wherever a `&str` is expected.
+impl Deref for Address {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Maps keyed by addresses can be queried with a `&str`.
+impl Borrow<str> for Address {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Address {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Address {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_string::deserialize(deserializer)
+    }
+}
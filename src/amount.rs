@@ -0,0 +1,91 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::MultiSendError;
+
+// Amount is a non-negative number of tokens, e.g. a holding, a transferred amount or a fee limit. Signed
+// values, like the coins of a balance change, stay plain i128s; `get` converts an amount to one for
+// calculations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Amount(i128);
+
+impl Amount {
+    pub const fn zero() -> Self {
+        Amount(0)
+    }
+
+    // new returns None if `amount` is negative.
+    pub const fn new(amount: i128) -> Option<Self> {
+        if amount < 0 {
+            None
+        } else {
+            Some(Amount(amount))
+        }
+    }
+
+    pub const fn get(&self) -> i128 {
+        self.0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(amount: u64) -> Self {
+        Amount(i128::from(amount))
+    }
+}
+
+impl TryFrom<i128> for Amount {
+    type Error = MultiSendError;
+
+    fn try_from(amount: i128) -> Result<Self, Self::Error> {
+        Amount::new(amount).ok_or_else(|| MultiSendError::InvalidAmount {
+            amount: amount.to_string(),
+        })
+    }
+}
+
+impl PartialEq<i128> for Amount {
+    fn eq(&self, other: &i128) -> bool {
+        self.0 == *other
+    }
+}
+
+impl FromStr for Amount {
+    type Err = MultiSendError;
+
+    // Parses a decimal integer such as "1000", without sign.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || MultiSendError::InvalidAmount {
+            amount: s.to_string(),
+        };
+        if s.is_empty() || !s.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        s.parse().map(Amount).map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// An amount is serialized as a string like the amount of a `Coin`, e.g. "1000".
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_string::deserialize(deserializer)
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::fee_policy::{FeePolicy, ProportionalFeePolicy};
+use crate::fees::add_amount;
+use crate::receipt::{MultiSendReceipt, SupplyChange};
+use crate::registry::DenomRegistry;
+use crate::state::Bank;
+use crate::types::{Balance, MultiSend};
+
+// BlockResult is the outcome of executing the transactions of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockResult {
+    // the balances after the last transaction, sorted by address and denom
+    pub final_balances: Vec<Balance>,
+    // the receipt or the rejection reason of every transaction, in the order of the transactions
+    pub tx_results: Vec<Result<MultiSendReceipt, MultiSendError>>,
+    // the burnt and commission amounts of all accepted transactions together, sorted by denom
+    pub totals: Vec<SupplyChange>,
+}
+
+// execute_block applies `txs` one after the other to `initial_balances`, every transaction sees the
+// balances left by the previous ones. A rejected transaction is recorded in the result and leaves the
+// balances unchanged, the following transactions are still executed. An error is only returned if the
+// initial balances are invalid.
+pub fn execute_block(
+    initial_balances: Vec<Balance>,
+    registry: DenomRegistry,
+    txs: Vec<MultiSend>,
+) -> Result<BlockResult, MultiSendError> {
+    execute_block_with_policy(
+        initial_balances,
+        registry,
+        txs,
+        Box::new(ProportionalFeePolicy),
+    )
+}
+
+// execute_block_with_policy works like `execute_block`, with the fees of every transaction decided by
+// `policy`. The bank of the block owns the policy, see `Bank::with_policy`.
+pub fn execute_block_with_policy(
+    initial_balances: Vec<Balance>,
+    registry: DenomRegistry,
+    txs: Vec<MultiSend>,
+    policy: Box<dyn FeePolicy>,
+) -> Result<BlockResult, MultiSendError> {
+    let mut bank = Bank::with_policy(registry, initial_balances, policy)?;
+    let tx_results: Vec<Result<MultiSendReceipt, MultiSendError>> = txs
+        .into_iter()
+        .map(|tx| bank.execute_multi_send(tx))
+        .collect();
+
+    // the bank keeps burnt and commission amounts within an i128, so the totals can not overflow.
+    let mut totals: BTreeMap<Denom, SupplyChange> = BTreeMap::new();
+    for receipt in tx_results.iter().flatten() {
+        for change in &receipt.supply_changes {
+            let total = totals
+                .entry(change.denom.clone())
+                .or_insert_with(|| SupplyChange {
+                    denom: change.denom.clone(),
+                    burned: 0,
+                    commission: 0,
+                });
+            add_amount(&mut total.burned, change.burned, &change.denom)?;
+            add_amount(&mut total.commission, change.commission, &change.denom)?;
+        }
+    }
+
+    Ok(BlockResult {
+        final_balances: bank.accounts(),
+        tx_results,
+        totals: totals.into_values().collect(),
+    })
+}
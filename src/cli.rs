@@ -0,0 +1,219 @@
+use std::fs;
+use std::io::{Read, Write};
+
+use serde::Deserialize;
+
+use crate::error::MultiSendError;
+use crate::fees::calculate_balance_changes;
+use crate::registry::DenomRegistry;
+use crate::types::{Balance, DenomDefinition, MultiSend};
+
+const USAGE: &str = "\
+usage: rust-task [--balances FILE --definitions FILE --tx FILE] [--format json|table]
+
+Evaluates a MultiSend transaction and prints the balance changes, or the reason it was rejected.
+Without files, a JSON bundle {\"balances\": [...], \"definitions\": [...], \"tx\": {...}} is read from stdin.
+
+exit status: 0 if the transaction is accepted, 1 if it is rejected, 2 on invalid arguments or input";
+
+// Exit statuses of the command-line tool.
+pub const EXIT_ACCEPTED: i32 = 0;
+pub const EXIT_REJECTED: i32 = 1;
+pub const EXIT_INVALID_INPUT: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Table,
+}
+
+// Options are the parsed command-line arguments.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    balances: Option<String>,
+    definitions: Option<String>,
+    tx: Option<String>,
+    format: Option<Format>,
+    help: bool,
+}
+
+// Bundle is everything needed to evaluate a transaction, as read from the JSON files or stdin.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bundle {
+    balances: Vec<Balance>,
+    definitions: Vec<DenomDefinition>,
+    tx: MultiSend,
+}
+
+impl Bundle {
+    pub fn new(balances: Vec<Balance>, definitions: Vec<DenomDefinition>, tx: MultiSend) -> Self {
+        Bundle {
+            balances,
+            definitions,
+            tx,
+        }
+    }
+
+    // registry validates the definitions of the bundle and registers them.
+    pub fn registry(&self) -> Result<DenomRegistry, MultiSendError> {
+        DenomRegistry::new(self.definitions.clone())
+    }
+}
+
+// run executes the command-line tool with the arguments (without the program name) and returns the exit
+// status. The result is written to `stdout`, problems with the arguments or the input to `stderr`.
+pub fn run(
+    args: impl IntoIterator<Item = String>,
+    stdin: impl Read,
+    stdout: &mut impl Write,
+    stderr: &mut impl Write,
+) -> i32 {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(message) => {
+            let _ = writeln!(stderr, "error: {message}\n\n{USAGE}");
+            return EXIT_INVALID_INPUT;
+        }
+    };
+    if options.help {
+        let _ = writeln!(stdout, "{USAGE}");
+        return EXIT_ACCEPTED;
+    }
+    let bundle = match load_bundle(&options, stdin) {
+        Ok(bundle) => bundle,
+        Err(message) => {
+            let _ = writeln!(stderr, "error: {message}");
+            return EXIT_INVALID_INPUT;
+        }
+    };
+
+    // invalid definitions are invalid input, not a rejected transaction.
+    let registry = match bundle.registry() {
+        Ok(registry) => registry,
+        Err(err) => {
+            let _ = writeln!(stderr, "error: invalid definitions: {err}");
+            return EXIT_INVALID_INPUT;
+        }
+    };
+
+    let result = evaluate(&registry, bundle);
+    let _ = writeln!(
+        stdout,
+        "{}",
+        render(&result, options.format.unwrap_or(Format::Json))
+    );
+    match result {
+        Ok(_) => EXIT_ACCEPTED,
+        Err(_) => EXIT_REJECTED,
+    }
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            options.help = true;
+            continue;
+        }
+        let mut value = || args.next().ok_or_else(|| format!("{arg} requires a value"));
+        let (slot, value) = match arg.as_str() {
+            "--balances" => (&mut options.balances, value()?),
+            "--definitions" => (&mut options.definitions, value()?),
+            "--tx" => (&mut options.tx, value()?),
+            "--format" => {
+                let format = match value()?.as_str() {
+                    "json" => Format::Json,
+                    "table" => Format::Table,
+                    other => return Err(format!("unknown format {other:?}")),
+                };
+                if options.format.replace(format).is_some() {
+                    return Err("--format given more than once".to_string());
+                }
+                continue;
+            }
+            other => return Err(format!("unknown argument {other:?}")),
+        };
+        if slot.replace(value).is_some() {
+            return Err(format!("{arg} given more than once"));
+        }
+    }
+    Ok(options)
+}
+
+// load_bundle reads the three JSON files given in the options, or a bundle from stdin if no file is given.
+pub fn load_bundle(options: &Options, mut stdin: impl Read) -> Result<Bundle, String> {
+    match (&options.balances, &options.definitions, &options.tx) {
+        (None, None, None) => {
+            let mut input = String::new();
+            stdin
+                .read_to_string(&mut input)
+                .map_err(|err| format!("failed to read stdin: {err}"))?;
+            serde_json::from_str(&input).map_err(|err| format!("invalid bundle on stdin: {err}"))
+        }
+        (Some(balances), Some(definitions), Some(tx)) => Ok(Bundle {
+            balances: read_json(balances)?,
+            definitions: read_json(definitions)?,
+            tx: read_json(tx)?,
+        }),
+        _ => Err("--balances, --definitions and --tx must be given together".to_string()),
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &str) -> Result<T, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("failed to read {path}: {err}"))?;
+    serde_json::from_str(&content).map_err(|err| format!("invalid JSON in {path}: {err}"))
+}
+
+// evaluate calculates the balance changes of the transaction of the bundle, with the definitions of
+// `registry` (see `Bundle::registry`).
+pub fn evaluate(registry: &DenomRegistry, bundle: Bundle) -> Result<Vec<Balance>, MultiSendError> {
+    calculate_balance_changes(bundle.balances, registry, bundle.tx)
+}
+
+// render formats the balance changes, or the rejection reason, for printing. In JSON the rejection is the
+// serialized `MultiSendError` with its message added, e.g. `{"error": {"kind": "InsufficientBalance",
+// "address": "account1", ..., "message": "account1 does not have enough balance ..."}}`.
+pub fn render(result: &Result<Vec<Balance>, MultiSendError>, format: Format) -> String {
+    match (result, format) {
+        (Ok(balance_changes), Format::Json) => {
+            serde_json::to_string_pretty(balance_changes).expect("balances serialize to JSON")
+        }
+        (Err(err), Format::Json) => {
+            let mut error = serde_json::to_value(err).expect("errors serialize to JSON");
+            error["message"] = err.to_string().into();
+            serde_json::json!({ "error": error }).to_string()
+        }
+        (Ok(balance_changes), Format::Table) => render_table(balance_changes),
+        (Err(err), Format::Table) => format!("rejected: {err}"),
+    }
+}
+
+// render_table prints one row per address and denom, with aligned columns.
+fn render_table(balance_changes: &[Balance]) -> String {
+    let mut rows = vec![(
+        "ADDRESS".to_string(),
+        "DENOM".to_string(),
+        "CHANGE".to_string(),
+    )];
+    for balance in balance_changes {
+        for coin in &balance.coins {
+            rows.push((
+                balance.address.to_string(),
+                coin.denom.to_string(),
+                coin.amount.to_string(),
+            ));
+        }
+    }
+    let address_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let denom_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+    let change_width = rows.iter().map(|row| row.2.len()).max().unwrap_or(0);
+    rows.iter()
+        .map(|(address, denom, change)| {
+            format!("{address:<address_width$}  {denom:<denom_width$}  {change:>change_width$}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
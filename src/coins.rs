@@ -0,0 +1,234 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::fees::add_amount;
+use crate::types::Coin;
+
+// Coins is a set of coins in canonical form (like `sdk.Coins` in cosmos sdk): sorted by denom, every denom
+// at most once and no zero amounts. Amounts are signed: `Balance` and `BalanceDelta` hold `Coins`, while
+// `AccountBalance` and `TransferLeg` keep a non-negative `Amount` per denom and convert to `Coins` with
+// `to_coins`. In JSON it is a list of `Coin`s, as text it is written like "1000denom1,5denom2"; both are
+// rejected if a denom appears twice or an amount is zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Coin>", into = "Vec<Coin>")]
+pub struct Coins(Vec<Coin>);
+
+impl Coins {
+    // new sorts `coins` by denom, like `NewCoins` in cosmos sdk. A denom that appears more than once or a zero
+    // amount is rejected, nothing is added up or left out.
+    pub fn new(coins: Vec<Coin>) -> Result<Self, MultiSendError> {
+        let mut sorted = Coins::default();
+        for coin in coins {
+            if coin.amount == 0 {
+                return Err(MultiSendError::ZeroAmount {
+                    denom: coin.denom.clone(),
+                });
+            }
+            match sorted.position(&coin.denom) {
+                Ok(_) => {
+                    return Err(MultiSendError::DuplicateDenom {
+                        denom: coin.denom.clone(),
+                    })
+                }
+                Err(index) => sorted.0.insert(index, coin),
+            }
+        }
+        Ok(sorted)
+    }
+
+    // normalized brings any list of coins into canonical form, for calculations: the amounts of a denom are
+    // added up, zero amounts are left out and the rest is sorted by denom.
+    pub fn normalized(coins: Vec<Coin>) -> Result<Self, MultiSendError> {
+        let mut sum = Coins::default();
+        for coin in coins {
+            sum.add_coin(coin)?;
+        }
+        Ok(sum)
+    }
+
+    // add_coin adds a single coin, a denom whose amount becomes zero is removed.
+    pub fn add_coin(&mut self, coin: Coin) -> Result<(), MultiSendError> {
+        match self.position(&coin.denom) {
+            Ok(index) => {
+                add_amount(&mut self.0[index].amount, coin.amount, &coin.denom)?;
+                if self.0[index].amount == 0 {
+                    self.0.remove(index);
+                }
+            }
+            Err(index) if coin.amount != 0 => self.0.insert(index, coin),
+            Err(_) => {}
+        }
+        Ok(())
+    }
+
+    // add returns `self + other`, where `other` may also be a list of coins that is not in canonical form.
+    pub fn add(&self, other: &[Coin]) -> Result<Coins, MultiSendError> {
+        let mut sum = self.clone();
+        for coin in other {
+            sum.add_coin(coin.clone())?;
+        }
+        Ok(sum)
+    }
+
+    // checked_sub returns `self - other`, or None if any amount of the result is negative or does not fit
+    // into an i128. Subtracting a holding from one that is not all greater or equal fails.
+    pub fn checked_sub(&self, other: &Coins) -> Option<Coins> {
+        let difference = self.add(&other.checked_neg()?).ok()?;
+        difference
+            .iter()
+            .all(|coin| coin.amount > 0)
+            .then_some(difference)
+    }
+
+    // checked_neg flips the sign of every amount, e.g. turns a debit into a balance change. None is returned
+    // if an amount is i128::MIN.
+    pub fn checked_neg(&self) -> Option<Coins> {
+        self.0
+            .iter()
+            .map(|coin| Some(Coin::new(coin.denom.clone(), coin.amount.checked_neg()?)))
+            .collect::<Option<Vec<Coin>>>()
+            .map(Coins)
+    }
+
+    // is_all_gte reports whether `self` has at least the amount of every coin of `other`.
+    pub fn is_all_gte(&self, other: &Coins) -> bool {
+        other
+            .iter()
+            .all(|coin| self.amount_of(&coin.denom) >= coin.amount)
+    }
+
+    // amount_of returns the amount of `denom`, zero if there is no coin of it.
+    pub fn amount_of(&self, denom: &str) -> i128 {
+        self.position(denom).map_or(0, |index| self.0[index].amount)
+    }
+
+    // retain keeps only the coins for which `keep` returns true.
+    pub fn retain(&mut self, keep: impl FnMut(&Coin) -> bool) {
+        self.0.retain(keep);
+    }
+
+    pub fn denoms(&self) -> impl Iterator<Item = &Denom> {
+        self.0.iter().map(|coin| &coin.denom)
+    }
+
+    fn position(&self, denom: &str) -> Result<usize, usize> {
+        self.0
+            .binary_search_by(|coin| coin.denom.as_str().cmp(denom))
+    }
+}
+
+// The coins can be read like a slice, e.g. `coins.len()` or `coins.iter()`.
+impl Deref for Coins {
+    type Target = [Coin];
+
+    fn deref(&self) -> &[Coin] {
+        &self.0
+    }
+}
+
+impl<'a> IntoIterator for &'a Coins {
+    type Item = &'a Coin;
+    type IntoIter = std::slice::Iter<'a, Coin>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for Coins {
+    type Item = Coin;
+    type IntoIter = std::vec::IntoIter<Coin>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl TryFrom<Vec<Coin>> for Coins {
+    type Error = MultiSendError;
+
+    fn try_from(coins: Vec<Coin>) -> Result<Self, Self::Error> {
+        Coins::new(coins)
+    }
+}
+
+impl From<Coin> for Coins {
+    fn from(coin: Coin) -> Self {
+        let mut coins = Coins::default();
+        // a single coin can not overflow.
+        coins.add_coin(coin).expect("a single coin is always valid");
+        coins
+    }
+}
+
+impl From<Coins> for Vec<Coin> {
+    fn from(coins: Coins) -> Self {
+        coins.0
+    }
+}
+
+impl FromStr for Coins {
+    type Err = MultiSendError;
+
+    // Parses a comma-separated list of coins like "1000denom1,5denom2", the empty string is no coins. Like
+    // `Coins::new`, a repeated denom or a zero amount is rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Coins::default());
+        }
+        let coins = s
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Coin>, MultiSendError>>()?;
+        Coins::new(coins)
+    }
+}
+
+impl fmt::Display for Coins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, coin) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{coin}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Coin {
+    type Err = MultiSendError;
+
+    // Parses an amount directly followed by a denom, e.g. "1000denom1" or "-120denom1". The amount must not
+    // have leading zeros.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || MultiSendError::InvalidCoin {
+            coin: s.to_string(),
+        };
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        let denom_start = s.len() - digits.len()
+            + digits
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+        let (amount, denom) = s.split_at(denom_start);
+        let digits = amount.strip_prefix('-').unwrap_or(amount);
+        if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) {
+            return Err(invalid());
+        }
+        Ok(Coin::new(
+            denom.parse()?,
+            amount.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+impl fmt::Display for Coin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.denom)
+    }
+}
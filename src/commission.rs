@@ -0,0 +1,70 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::types::DenomDefinition;
+
+// CommissionBeneficiary receives `weight` parts of the commission of a denom, e.g. beneficiaries with the
+// weights 3, 1 and 1 receive 60%, 20% and 20%.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommissionBeneficiary {
+    pub address: Address,
+    pub weight: u64,
+}
+
+impl DenomDefinition {
+    // split_commission distributes the commission `amount` collected in a transaction between the
+    // beneficiaries of the definition, or gives it to the issuer if it has none. The shares always add up
+    // to `amount`: every beneficiary first gets its share rounded down, then the remaining units go one by
+    // one to the beneficiaries with the largest rounded-off fractions, and on a tie to the one listed
+    // first (see `split_by_weight`). The shares are returned in the order of the beneficiaries, including
+    // zero shares.
+    pub fn split_commission(&self, amount: i128) -> Vec<(Address, i128)> {
+        if self.commission_beneficiaries.is_empty() {
+            return vec![(self.issuer.clone(), amount)];
+        }
+        let weights: Vec<i128> = self
+            .commission_beneficiaries
+            .iter()
+            .map(|beneficiary| i128::from(beneficiary.weight))
+            .collect();
+        self.commission_beneficiaries
+            .iter()
+            .map(|beneficiary| beneficiary.address.clone())
+            .zip(split_by_weight(amount, &weights))
+            .collect()
+    }
+}
+
+// split_by_weight splits the non-negative `amount` in proportion to the non-negative `weights` so that the
+// shares add up to exactly `amount`: every share is first rounded down, then the remaining units go one by
+// one to the largest rounded-off fractions, and on a tie to the first weight. All shares are zero if the
+// weights are.
+pub(crate) fn split_by_weight(amount: i128, weights: &[i128]) -> Vec<i128> {
+    let total_weight: BigInt = weights.iter().map(|weight| BigInt::from(*weight)).sum();
+    if total_weight == BigInt::from(0) {
+        return vec![0; weights.len()];
+    }
+
+    let mut shares: Vec<i128> = Vec::new();
+    let mut fractions: Vec<(BigInt, usize)> = Vec::new();
+    let mut remainder = amount;
+    for (index, weight) in weights.iter().enumerate() {
+        let (share, fraction) =
+            (BigInt::from(amount) * BigInt::from(*weight)).div_mod_floor(&total_weight);
+        // a share is never larger than `amount`, so it fits into an i128.
+        let share = i128::try_from(share).expect("share is at most the amount");
+        remainder -= share;
+        shares.push(share);
+        fractions.push((fraction, index));
+    }
+
+    // less units remain than there are weights.
+    fractions.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, index) in fractions.into_iter().take(remainder as usize) {
+        shares[index] += 1;
+    }
+    shares
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Decimal is a non-negative fixed-point number with 18 fractional digits (like cosmos `sdk.Dec`).
+// It is stored as an integer number of 10^-18 units ("atomics"), so rates such as `0.08` are exact
+// and fee shares can be calculated in integer arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Decimal(u128);
+
+impl Decimal {
+    pub const DECIMAL_PLACES: u32 = 18;
+    // the number of atomics in 1.0
+    pub const FRACTIONAL: u128 = 10u128.pow(Self::DECIMAL_PLACES);
+
+    pub const fn zero() -> Self {
+        Decimal(0)
+    }
+
+    pub const fn one() -> Self {
+        Decimal(Self::FRACTIONAL)
+    }
+
+    // from_atomics creates a decimal from its raw representation, e.g. `from_atomics(8 * 10^16)` is 0.08.
+    pub const fn from_atomics(atomics: u128) -> Self {
+        Decimal(atomics)
+    }
+
+    // percent creates a decimal from a whole percentage, e.g. `percent(8)` is 0.08.
+    pub const fn percent(percent: u64) -> Self {
+        Decimal(percent as u128 * (Self::FRACTIONAL / 100))
+    }
+
+    pub const fn atomics(&self) -> u128 {
+        self.0
+    }
+
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+// DecimalParseError is returned when a string is not a valid non-negative decimal with at most 18
+// fractional digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalParseError {
+    input: String,
+}
+
+impl fmt::Display for DecimalParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid decimal: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for DecimalParseError {}
+
+impl FromStr for Decimal {
+    type Err = DecimalParseError;
+
+    // Parses strings such as "1", "0.08" or "0.000000000000000001".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || DecimalParseError {
+            input: s.to_string(),
+        };
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if whole.is_empty()
+            || !is_digits(whole)
+            || !is_digits(fraction)
+            || (s.contains('.') && fraction.is_empty())
+            || fraction.len() > Self::DECIMAL_PLACES as usize
+        {
+            return Err(error());
+        }
+
+        let whole: u128 = whole.parse().map_err(|_| error())?;
+        let fraction_atomics: u128 = if fraction.is_empty() {
+            0
+        } else {
+            let scale = 10u128.pow(Self::DECIMAL_PLACES - fraction.len() as u32);
+            fraction.parse::<u128>().map_err(|_| error())? * scale
+        };
+        whole
+            .checked_mul(Self::FRACTIONAL)
+            .and_then(|atomics| atomics.checked_add(fraction_atomics))
+            .map(Decimal)
+            .ok_or_else(error)
+    }
+}
+
+impl fmt::Display for Decimal {
+    // Prints the shortest exact representation, e.g. "0.08" or "1".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 / Self::FRACTIONAL;
+        let fraction = self.0 % Self::FRACTIONAL;
+        if fraction == 0 {
+            return write!(f, "{whole}");
+        }
+        let fraction = format!("{:018}", fraction);
+        write!(f, "{}.{}", whole, fraction.trim_end_matches('0'))
+    }
+}
+
+// A decimal is serialized as its exact string representation, e.g. "0.08".
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_string::deserialize(deserializer)
+    }
+}
@@ -0,0 +1,79 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::MultiSendError;
+use crate::validation::is_valid_denom;
+
+// Denom is the name of a coin type in the cosmos sdk format, see `is_valid_denom`. Examples are "denom1",
+// "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2" and "factory/creator/subdenom".
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Denom(String);
+
+impl Denom {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Denom {
+    type Err = MultiSendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !is_valid_denom(s) {
+            return Err(MultiSendError::InvalidDenom {
+                denom: s.to_string(),
+            });
+        }
+        Ok(Denom(s.to_string()))
+    }
+}
+
+impl fmt::Display for Denom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// A `&Denom` can be passed wherever a `&str` is expected.
+impl Deref for Denom {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Maps keyed by denoms can be queried with a `&str`.
+impl Borrow<str> for Denom {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Denom {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Denom {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Serialize for Denom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_string::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Denom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde_string::deserialize(deserializer)
+    }
+}
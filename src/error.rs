@@ -0,0 +1,226 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::address::Address;
+use crate::amount::Amount;
+use crate::decimal::Decimal;
+use crate::denom::Denom;
+use crate::exemption::Fee;
+use crate::serde_string;
+
+// MultiSendError is the reason a `MultiSend` transaction was rejected by `calculate_balance_changes`.
+// Every variant carries the offending data, so callers can decide whether to retry, reject or alert
+// without matching on error messages. Addresses and denoms are typed, except the malformed ones of
+// `InvalidAddress` and `InvalidDenom`, and so are sums that can not be negative. The unparsed text of
+// `InvalidAmount` and `InvalidCoin` stays a string, and amounts that may be negative stay an i128: the
+// non-positive amount of `NonPositiveAmount`, the negative balance of `NegativeBalance` and the amounts of
+// `InsufficientBalance`, as original balances passed as a `Balance` are not checked for negative amounts.
+// In JSON an error is an object with the name of the variant as "kind" and its fields, amounts as strings
+// like everywhere else, e.g. `{"kind": "Overflow", "denom": "denom1"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind")]
+pub enum MultiSendError {
+    // The sum of inputs and the sum of outputs for `denom` are not equal.
+    InputOutputMismatch {
+        denom: Denom,
+        input_sum: Amount,
+        output_sum: Amount,
+    },
+    // `address` must pay `required` (sent amount + burn + commission) of `denom` but only holds `available`.
+    InsufficientBalance {
+        address: Address,
+        denom: Denom,
+        #[serde(serialize_with = "serde_string::serialize")]
+        required: i128,
+        #[serde(serialize_with = "serde_string::serialize")]
+        available: i128,
+    },
+    // The transaction has no inputs.
+    EmptyInputs,
+    // The transaction has no outputs.
+    EmptyOutputs,
+    // `address` appears more than once in the inputs.
+    DuplicateInputAddress {
+        address: Address,
+    },
+    // `address` appears more than once in the outputs.
+    DuplicateOutputAddress {
+        address: Address,
+    },
+    // `address` sends or receives no coins, e.g. because all of its amounts are zero.
+    EmptyCoins {
+        address: Address,
+    },
+    // `denom` appears more than once in a list of coins.
+    DuplicateDenom {
+        denom: Denom,
+    },
+    // `denom` has a zero amount in a list of coins, e.g. "0denom1".
+    ZeroAmount {
+        denom: Denom,
+    },
+    // A coin amount is zero or negative.
+    NonPositiveAmount {
+        address: Address,
+        denom: Denom,
+        #[serde(serialize_with = "serde_string::serialize")]
+        amount: i128,
+    },
+    // `denom` is not a well-formed denom.
+    InvalidDenom {
+        denom: String,
+    },
+    // `address` is not a well-formed address.
+    InvalidAddress {
+        address: String,
+    },
+    // `amount` is not a non-negative integer.
+    InvalidAmount {
+        amount: String,
+    },
+    // `coin` is not an amount followed by a denom, like "1000denom1".
+    InvalidCoin {
+        coin: String,
+    },
+    // An account balance is negative.
+    NegativeBalance {
+        address: Address,
+        denom: Denom,
+        #[serde(serialize_with = "serde_string::serialize")]
+        amount: i128,
+    },
+    // An input address has no entry in the original balances.
+    MissingOriginalBalance {
+        address: Address,
+    },
+    // The transaction uses denoms that have no `DenomDefinition`.
+    UnknownDenoms {
+        denoms: Vec<Denom>,
+    },
+    // `denom` has more than one `DenomDefinition`.
+    DuplicateDefinition {
+        denom: Denom,
+    },
+    // The burn or commission rate of `denom` is above 1.
+    InvalidRate {
+        denom: Denom,
+        rate: Decimal,
+    },
+    // The rate tiers of the `fee` of `denom` do not start above zero or are not strictly ascending.
+    InvalidRateTiers {
+        denom: Denom,
+        fee: Fee,
+    },
+    // The minimum of the `fee` of `denom` is above the maximum.
+    InvalidFeeLimits {
+        denom: Denom,
+        fee: Fee,
+    },
+    // A commission beneficiary of `denom` has a weight of zero.
+    ZeroBeneficiaryWeight {
+        denom: Denom,
+        address: Address,
+    },
+    // `address` is listed more than once as commission beneficiary of `denom`.
+    DuplicateBeneficiary {
+        denom: Denom,
+        address: Address,
+    },
+    // The `FeePolicy` did not return one non-negative share for every input and output of `denom`.
+    InvalidFeeShares {
+        denom: Denom,
+    },
+    // An amount of `denom` does not fit into an i128 while calculating the balance changes.
+    Overflow {
+        denom: Denom,
+    },
+}
+
+impl fmt::Display for MultiSendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiSendError::InputOutputMismatch {
+                denom,
+                input_sum,
+                output_sum,
+            } => write!(
+                f,
+                "input and output does not match for {denom}: inputs {input_sum}, outputs {output_sum}"
+            ),
+            MultiSendError::InsufficientBalance {
+                address,
+                denom,
+                required,
+                available,
+            } => write!(
+                f,
+                "{address} does not have enough balance for {denom}: required {required}, available {available}"
+            ),
+            MultiSendError::EmptyInputs => write!(f, "transaction has no inputs"),
+            MultiSendError::EmptyOutputs => write!(f, "transaction has no outputs"),
+            MultiSendError::DuplicateInputAddress { address } => {
+                write!(f, "{address} appears more than once in the inputs")
+            }
+            MultiSendError::DuplicateOutputAddress { address } => {
+                write!(f, "{address} appears more than once in the outputs")
+            }
+            MultiSendError::EmptyCoins { address } => {
+                write!(f, "{address} sends or receives no coins")
+            }
+            MultiSendError::DuplicateDenom { denom } => {
+                write!(f, "{denom} appears more than once in the coins")
+            }
+            MultiSendError::ZeroAmount { denom } => {
+                write!(f, "the amount of {denom} in the coins is zero")
+            }
+            MultiSendError::NonPositiveAmount {
+                address,
+                denom,
+                amount,
+            } => write!(f, "amount {amount} of {denom} for {address} is not positive"),
+            MultiSendError::InvalidDenom { denom } => write!(f, "invalid denom {denom:?}"),
+            MultiSendError::InvalidAddress { address } => write!(f, "invalid address {address:?}"),
+            MultiSendError::InvalidAmount { amount } => write!(f, "invalid amount {amount:?}"),
+            MultiSendError::InvalidCoin { coin } => write!(f, "invalid coin {coin:?}"),
+            MultiSendError::NegativeBalance {
+                address,
+                denom,
+                amount,
+            } => write!(f, "balance {amount} of {denom} for {address} is negative"),
+            MultiSendError::MissingOriginalBalance { address } => {
+                write!(f, "no original balance specified for {address}")
+            }
+            MultiSendError::UnknownDenoms { denoms } => {
+                let denoms: Vec<String> = denoms.iter().map(Denom::to_string).collect();
+                write!(f, "no definition found for denoms: {}", denoms.join(", "))
+            }
+            MultiSendError::DuplicateDefinition { denom } => {
+                write!(f, "{denom} is defined more than once")
+            }
+            MultiSendError::InvalidRate { denom, rate } => {
+                write!(f, "invalid rate {rate} for {denom}")
+            }
+            MultiSendError::InvalidRateTiers { denom, fee } => {
+                write!(f, "{fee} rate tiers of {denom} must start above zero and be ascending")
+            }
+            MultiSendError::InvalidFeeLimits { denom, fee } => {
+                write!(f, "invalid {fee} limits for {denom}")
+            }
+            MultiSendError::ZeroBeneficiaryWeight { denom, address } => {
+                write!(f, "commission beneficiary {address} of {denom} has a weight of zero")
+            }
+            MultiSendError::DuplicateBeneficiary { denom, address } => {
+                write!(f, "{address} is a commission beneficiary of {denom} more than once")
+            }
+            MultiSendError::InvalidFeeShares { denom } => {
+                write!(f, "the fee policy returned invalid fee shares for {denom}")
+            }
+            MultiSendError::Overflow { denom } => {
+                write!(f, "amount overflow while calculating balance changes for {denom}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultiSendError {}
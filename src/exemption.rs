@@ -0,0 +1,72 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::types::DenomDefinition;
+
+// Fee is one of the two fees charged on a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fee {
+    Burn,
+    Commission,
+}
+
+impl Fee {
+    pub const ALL: [Fee; 2] = [Fee::Burn, Fee::Commission];
+}
+
+impl fmt::Display for Fee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fee::Burn => write!(f, "burn"),
+            Fee::Commission => write!(f, "commission"),
+        }
+    }
+}
+
+// Direction tells whether an address takes part in a transfer as an input or as an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Sending,
+    Receiving,
+}
+
+// FeeExemption exempts `address` from the fees of a denom, like its issuer. An exempt input pays no fee
+// and an exempt output does not count towards the amount the fee is charged on.
+// In JSON only the address is required, e.g. `{"address": "treasury", "fee": "burn", "direction": "sending"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeExemption {
+    pub address: Address,
+    // the exempt fee, or both fees if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<Fee>,
+    // the exempt direction, or both directions if None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+}
+
+impl FeeExemption {
+    fn applies(&self, address: &str, fee: Fee, direction: Direction) -> bool {
+        self.address == address
+            && self.fee.is_none_or(|exempt_fee| exempt_fee == fee)
+            && self
+                .direction
+                .is_none_or(|exempt_direction| exempt_direction == direction)
+    }
+}
+
+impl DenomDefinition {
+    // is_exempt reports whether `address` does not pay `fee` when it is sending or receiving this denom.
+    // The issuer is always exempt from both fees.
+    pub fn is_exempt(&self, address: &str, fee: Fee, direction: Direction) -> bool {
+        self.issuer == address
+            || self
+                .exemptions
+                .iter()
+                .any(|exemption| exemption.applies(address, fee, direction))
+    }
+}
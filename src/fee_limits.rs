@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::exemption::Fee;
+use crate::types::DenomDefinition;
+
+// FeeLimits bound the burn or the commission of a denom that a whole transaction pays, before it is split
+// between the inputs. The minimum only applies if the fee is charged at all, i.e. if some non-exempt
+// input sends to some non-exempt output. A bounded fee is paid exactly, the shares of the inputs are not
+// rounded up beyond it. In JSON both bounds are optional amount strings, e.g.
+// `{"min": "1", "max": "1000"}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Amount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Amount>,
+}
+
+impl FeeLimits {
+    pub fn is_empty(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+
+    // is_valid reports whether the minimum is not above the maximum.
+    pub fn is_valid(&self) -> bool {
+        !matches!((self.min, self.max), (Some(min), Some(max)) if min > max)
+    }
+}
+
+impl DenomDefinition {
+    pub fn limits(&self, fee: Fee) -> FeeLimits {
+        match fee {
+            Fee::Burn => self.burn_limits,
+            Fee::Commission => self.commission_limits,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+
+use crate::address::Address;
+use crate::commission::split_by_weight;
+use crate::decimal::Decimal;
+use crate::error::MultiSendError;
+use crate::exemption::{Direction, Fee};
+use crate::fee_limits::FeeLimits;
+use crate::fees::add_amount;
+use crate::types::DenomDefinition;
+
+// DenomTransfers is what a transaction moves of one denom: the amount of every input and output that has a
+// coin of the denom, in the order of the transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenomTransfers<'a> {
+    pub definition: &'a DenomDefinition,
+    pub inputs: Vec<(&'a Address, i128)>,
+    pub outputs: Vec<(&'a Address, i128)>,
+}
+
+// FeeShare is the burn and the commission one input or output pays for a denom.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeShare {
+    pub burn: i128,
+    pub commission: i128,
+}
+
+// FeeShares are the fees of all inputs and outputs of a denom, in the order of `DenomTransfers`. An input
+// pays its share on top of the amount it sends, an output pays it out of the amount it receives.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeShares {
+    pub inputs: Vec<FeeShare>,
+    pub outputs: Vec<FeeShare>,
+}
+
+// FeePolicy decides the burn and commission of a transaction, one denom at a time. The balance-change engine
+// (`calculate_multi_send_receipt_with_policy`) takes care of everything else: validation, the balance
+// checks, paying the commission to the issuer or beneficiaries and the receipt. Shares must be given for
+// every input and output and must not be negative, otherwise the transaction is rejected with
+// `InvalidFeeShares`.
+pub trait FeePolicy {
+    fn fee_shares(&self, transfers: &DenomTransfers<'_>) -> Result<FeeShares, MultiSendError>;
+}
+
+// ProportionalFeePolicy is the default policy used by `calculate_balance_changes`, see its comment: per fee,
+// the amount the fee is charged on is the minimum of the non-exempt input and output sums, the rate comes
+// from the flat rate or the rate tiers, the fee is bounded by the limits and it is split between the
+// non-exempt inputs in proportion to their amounts, rounded up. Outputs pay nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProportionalFeePolicy;
+
+impl FeePolicy for ProportionalFeePolicy {
+    fn fee_shares(&self, transfers: &DenomTransfers<'_>) -> Result<FeeShares, MultiSendError> {
+        let definition = transfers.definition;
+        let denom = &definition.denom;
+        let overflow = || MultiSendError::Overflow {
+            denom: denom.clone(),
+        };
+
+        //calculate, for every fee, the sum of input and output amounts of the accounts that are not exempt
+        //from it (the issuer and the exemptions of the definition)
+        let charged_sum = |legs: &[(&Address, i128)], fee: Fee, direction: Direction| {
+            let mut sum: i128 = 0;
+            for (address, amount) in legs {
+                if !definition.is_exempt(address, fee, direction) {
+                    add_amount(&mut sum, *amount, denom)?;
+                }
+            }
+            Ok::<i128, MultiSendError>(sum)
+        };
+
+        let mut shares = FeeShares {
+            inputs: vec![FeeShare::default(); transfers.inputs.len()],
+            outputs: vec![FeeShare::default(); transfers.outputs.len()],
+        };
+        for fee in Fee::ALL {
+            let charged_input_amount = charged_sum(&transfers.inputs, fee, Direction::Sending)?;
+            let charged_output_amount = charged_sum(&transfers.outputs, fee, Direction::Receiving)?;
+            let min_amount = charged_input_amount.min(charged_output_amount);
+            let rate = definition.rate(fee, min_amount);
+            let charged_amounts: Vec<i128> = transfers
+                .inputs
+                .iter()
+                .map(|(address, amount)| {
+                    if definition.is_exempt(address, fee, Direction::Sending) {
+                        0
+                    } else {
+                        *amount
+                    }
+                })
+                .collect();
+            let fee_amounts = split_fee(
+                min_amount,
+                rate,
+                definition.limits(fee),
+                &charged_amounts,
+                charged_input_amount,
+            )
+            .ok_or_else(overflow)?;
+            for (share, fee_amount) in shares.inputs.iter_mut().zip(fee_amounts) {
+                match fee {
+                    Fee::Burn => share.burn = fee_amount,
+                    Fee::Commission => share.commission = fee_amount,
+                }
+            }
+        }
+        Ok(shares)
+    }
+}
+
+// split_fee calculates the shares of the input accounts in a burn or commission. The fee of the transaction is
+// total * rate, and every input pays roundup(fee * amount / non_issuer_input_sum). If the fee is outside
+// `limits`, the transaction pays exactly the limit instead, split in proportion to the amounts with
+// `split_by_weight`; the same happens if rounding up the shares would exceed the maximum. The calculation is
+// done on big integers, so the result is exact for every i128 input. None is returned if a share does not
+// fit into an i128.
+fn split_fee(
+    total: i128,
+    rate: Decimal,
+    limits: FeeLimits,
+    amounts: &[i128],
+    non_issuer_input_sum: i128,
+) -> Option<Vec<i128>> {
+    if non_issuer_input_sum == 0 || total == 0 {
+        return Some(vec![0; amounts.len()]);
+    }
+    // the fee of the whole transaction, in 10^-18 units
+    let fractional = BigInt::from(Decimal::FRACTIONAL);
+    let fee = BigInt::from(total) * BigInt::from(rate.atomics());
+    let bound = match (limits.min, limits.max) {
+        (Some(min), _) if fee < BigInt::from(min.get()) * &fractional => Some(min),
+        (_, Some(max)) if fee > BigInt::from(max.get()) * &fractional => Some(max),
+        _ => None,
+    };
+    if let Some(limit) = bound {
+        return Some(split_by_weight(limit.get(), amounts));
+    }
+
+    let denominator = fractional * BigInt::from(non_issuer_input_sum);
+    let shares = amounts
+        .iter()
+        .map(|amount| {
+            let numerator = &fee * BigInt::from(*amount);
+            i128::try_from(Integer::div_ceil(&numerator, &denominator)).ok()
+        })
+        .collect::<Option<Vec<i128>>>()?;
+    match limits.max {
+        Some(max)
+            if shares
+                .iter()
+                .map(|share| BigInt::from(*share))
+                .sum::<BigInt>()
+                > BigInt::from(max.get()) =>
+        {
+            Some(split_by_weight(max.get(), amounts))
+        }
+        _ => Some(shares),
+    }
+}
@@ -0,0 +1,499 @@
+use crate::address::Address;
+use crate::amount::Amount;
+use crate::coins::Coins;
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::fee_policy::{DenomTransfers, FeePolicy, FeeShare, ProportionalFeePolicy};
+use crate::receipt::{CommissionPayment, FeeLine, MultiSendReceipt, OutputFeeLine, SupplyChange};
+use crate::registry::DenomRegistry;
+use crate::types::{AccountBalance, Balance, BalanceDelta, Coin, MultiSend};
+use crate::validation;
+use std::collections::BTreeMap;
+
+// FundingMode decides which funds an input may use to pay its debit, which matters when an address is both
+// an input and an output (or the issuer receiving a commission) of the same transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FundingMode {
+    // The debit (sent amount + burn + commission) must be covered by the original balance alone, as if all
+    // inputs were deducted before any output is credited (like the bank module of cosmos sdk).
+    #[default]
+    OriginalBalance,
+    // Credits of the same transaction fund the debit, only the final balance of the address
+    // (original balance + net change) must not be negative.
+    WithCredits,
+}
+
+// Implement `calculate_balance_changes` with the following requirements.
+// - Output of the function is the balance changes that must be applied to different accounts
+//   (negative means deduction, positive means addition), or an error. the error indicates that the transaction must be rejected.
+// - If sum of inputs and outputs in multi_send_tx does not match the tx must be rejected(i.e return error).
+// - Apply burn_rate and commission_rate as described by their definition.
+// - If the sender does not have enough balances (in the original_balances) to cover the input amount on top of burn_rate and
+// commission_rate, the transaction must be rejected.
+// - burn_rate and commission_rate does not apply to the issuer (nor to the `exemptions` of the definition, which
+//   are handled like the issuer, separately for every fee and direction). So to calculate the correct values you must do this for every denom:
+//      - sum all the inputs coming from accounts that are not an issuer (let's call it non_issuer_input_sum)
+//      - sum all the outputs going to accounts that are not an issuer (let's call it non_issuer_output_sum)
+//      - total burn amount is total_burn = min(non_issuer_input_sum, non_issuer_output_sum)
+//      - if the definition has `burn_tiers`, the rate is the one of the last tier whose `from` is not above
+//        total_burn, or burn_rate if total_burn is below all tiers
+//      - if the definition has `burn_limits`, total_burn is raised to the minimum (unless it is zero because
+//        nothing is charged) or lowered to the maximum
+//      - total_burn is distributed between all input accounts as: account_share = roundup(total_burn * input_from_account / non_issuer_input_sum)
+//        (a total_burn bounded by `burn_limits` is instead split exactly, with the remaining units going to the
+//        largest rounded-off fractions, and so is the maximum if the rounded-up shares would exceed it)
+//      - total_burn_amounts = sum (account_shares) // notice that in previous step we rounded up, so we need to recalculate the total again.
+//      - commission_rate is exactly the same, but we send the calculate value to issuer, and not burn
+//        (or split it between the `commission_beneficiaries` of the definition).
+//      - Example:
+//          burn_rate: 10%
+//
+//          inputs:
+//          60, 90
+//          25 <-- issuer
+//
+//          outputs:
+//          50
+//          100 <-- issuer
+//          25
+//          In this case burn amount is: min(non_issuer_inputs, non_issuer_outputs) = min(75+75, 50+25) = 75
+//          Expected burn: 75 * 10% = 7.5
+//          And now we divide it proportionally between all input sender: first_sender_share  = 7.5 * 60 / 150  = 3
+//                                                                        second_sender_share = 7.5 * 90 / 150  = 4.5
+// - Entries of `original_balances` with the same address are added up. An address may appear at most once in
+//   the inputs and once in the outputs; if it appears in both, it pays the fees on its input like any other
+//   sender and its balance change is the net of debit and credit. See `FundingMode` for the balance check.
+// - The result is deterministic: balance changes are sorted by address and the coins of every address are
+//   sorted by denom. Addresses and denoms without a change are left out.
+// - In README.md we have provided more examples to help you better understand the requirements.
+// - Write different unit tests to cover all the edge cases, we would like to see how you structure your tests.
+//   There are examples in README.md, you can convert them into tests, but you should add more cases.
+pub fn calculate_balance_changes(
+    original_balances: Vec<Balance>,
+    registry: &DenomRegistry,
+    multi_send_tx: MultiSend,
+) -> Result<Vec<Balance>, MultiSendError> {
+    calculate_multi_send_receipt(original_balances, registry, multi_send_tx)
+        .map(MultiSendReceipt::into_balance_changes)
+}
+
+// calculate_balance_deltas is `calculate_balance_changes` with types that separate holdings from changes:
+// the original balances are known to be non-negative and the result can be passed to `state::apply_deltas`.
+pub fn calculate_balance_deltas(
+    original_balances: Vec<AccountBalance>,
+    registry: &DenomRegistry,
+    multi_send_tx: MultiSend,
+) -> Result<Vec<BalanceDelta>, MultiSendError> {
+    let original_balances = original_balances.into_iter().map(Balance::from).collect();
+    let balance_changes = calculate_balance_changes(original_balances, registry, multi_send_tx)?;
+    Ok(balance_changes
+        .into_iter()
+        .map(BalanceDelta::from)
+        .collect())
+}
+
+// calculate_balance_changes_reporting_all works like `calculate_balance_changes`, but a rejected
+// transaction is reported with all of its problems (see `validation::collect_errors`) instead of only the
+// first one.
+pub fn calculate_balance_changes_reporting_all(
+    original_balances: Vec<Balance>,
+    registry: &DenomRegistry,
+    multi_send_tx: MultiSend,
+) -> Result<Vec<Balance>, Vec<MultiSendError>> {
+    let errors = validation::collect_errors(&original_balances, registry, &multi_send_tx);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    calculate_balance_changes(original_balances, registry, multi_send_tx).map_err(|err| vec![err])
+}
+
+// calculate_multi_send_receipt works like `calculate_balance_changes`, but returns a receipt that also
+// breaks the debit of every input down into the transferred amount, the burn and the commission.
+pub fn calculate_multi_send_receipt(
+    original_balances: Vec<Balance>,
+    registry: &DenomRegistry,
+    multi_send_tx: MultiSend,
+) -> Result<MultiSendReceipt, MultiSendError> {
+    calculate_multi_send_receipt_with_funding(
+        original_balances,
+        registry,
+        multi_send_tx,
+        FundingMode::default(),
+    )
+}
+
+// calculate_multi_send_receipt_with_funding works like `calculate_multi_send_receipt`, with the balance
+// check chosen by `funding`.
+pub fn calculate_multi_send_receipt_with_funding(
+    original_balances: Vec<Balance>,
+    registry: &DenomRegistry,
+    multi_send_tx: MultiSend,
+    funding: FundingMode,
+) -> Result<MultiSendReceipt, MultiSendError> {
+    calculate_multi_send_receipt_with_policy(
+        original_balances,
+        registry,
+        multi_send_tx,
+        funding,
+        &ProportionalFeePolicy,
+    )
+}
+
+// calculate_multi_send_receipt_with_policy works like `calculate_multi_send_receipt_with_funding`, with the
+// burn and commission of every denom decided by `policy` instead of `ProportionalFeePolicy`. An output that
+// pays a fee receives the transferred amount minus its fee, the transaction is rejected if the fee is larger
+// than that amount.
+pub fn calculate_multi_send_receipt_with_policy(
+    original_balances: Vec<Balance>,
+    registry: &DenomRegistry,
+    multi_send_tx: MultiSend,
+    funding: FundingMode,
+    policy: &(impl FeePolicy + ?Sized),
+) -> Result<MultiSendReceipt, MultiSendError> {
+    let original_amounts = sum_balances(&original_balances)?;
+    let (fee_lines, output_fee_lines) = calculate_fees(registry, &multi_send_tx, policy)?;
+
+    //check that every input can pay its fee lines
+    let mut burn_amounts: BTreeMap<Denom, i128> = BTreeMap::new();
+    let mut commission_amounts: BTreeMap<Denom, i128> = BTreeMap::new();
+    let mut blance_changes: BTreeMap<Address, Coins> = BTreeMap::new();
+
+    for input in &multi_send_tx.inputs {
+        let mut coins = Coins::default();
+        let balance_coins = original_amounts.get(&input.address).ok_or_else(|| {
+            MultiSendError::MissingOriginalBalance {
+                address: input.address.clone(),
+            }
+        })?;
+        // input addresses are unique, so these are exactly the fee lines of this input.
+        for line in fee_lines
+            .iter()
+            .filter(|line| line.address == input.address)
+        {
+            let denom = &line.denom;
+            let available = balance_coins.amount_of(denom);
+            if funding == FundingMode::OriginalBalance && available < line.total_debited {
+                return Err(MultiSendError::InsufficientBalance {
+                    address: input.address.clone(),
+                    denom: denom.clone(),
+                    required: line.total_debited,
+                    available,
+                });
+            }
+            coins.add_coin(Coin::new(
+                denom.clone(),
+                line.total_debited
+                    .checked_neg()
+                    .ok_or_else(|| MultiSendError::Overflow {
+                        denom: denom.clone(),
+                    })?,
+            ))?;
+            let total_burn_amount = burn_amounts.entry(denom.clone()).or_insert(0);
+            add_amount(total_burn_amount, line.burn, denom)?;
+            let total_commission_amount = commission_amounts.entry(denom.clone()).or_insert(0);
+            add_amount(total_commission_amount, line.commission, denom)?;
+        }
+        blance_changes.insert(input.address.clone(), coins);
+    }
+
+    for output in &multi_send_tx.outputs {
+        let address = &output.address;
+        let change_coins = blance_changes.entry(address.clone()).or_default();
+        *change_coins = change_coins.add(&output.coins)?;
+    }
+
+    //outputs pay their fees out of the amount they receive
+    for line in &output_fee_lines {
+        let denom = &line.denom;
+        let change_coins = blance_changes.entry(line.address.clone()).or_default();
+        change_coins.add_coin(Coin::new(
+            denom.clone(),
+            line.total_credited - line.received,
+        ))?;
+        let total_burn_amount = burn_amounts.entry(denom.clone()).or_insert(0);
+        add_amount(total_burn_amount, line.burn, denom)?;
+        let total_commission_amount = commission_amounts.entry(denom.clone()).or_insert(0);
+        add_amount(total_commission_amount, line.commission, denom)?;
+    }
+
+    //update balance_changes for issuers and commission beneficiaries.
+    let mut commissions: Vec<CommissionPayment> = Vec::new();
+    for (denom, amount) in &commission_amounts {
+        if *amount == 0 {
+            continue;
+        }
+        let definition = registry
+            .get(denom)
+            .ok_or_else(|| MultiSendError::UnknownDenoms {
+                denoms: vec![denom.clone()],
+            })?;
+        for (address, share) in definition.split_commission(*amount) {
+            if share == 0 {
+                continue;
+            }
+            let change_coins = blance_changes.entry(address.clone()).or_default();
+            change_coins.add_coin(Coin::new(denom.clone(), share))?;
+            commissions.push(CommissionPayment {
+                address,
+                denom: denom.clone(),
+                amount: share,
+            });
+        }
+    }
+
+    if funding == FundingMode::WithCredits {
+        for line in &fee_lines {
+            let overflow = || MultiSendError::Overflow {
+                denom: line.denom.clone(),
+            };
+            let available = original_amounts[&line.address].amount_of(&line.denom);
+            let change = blance_changes[&line.address].amount_of(&line.denom);
+            if available.checked_add(change).ok_or_else(overflow)? < 0 {
+                return Err(MultiSendError::InsufficientBalance {
+                    address: line.address.clone(),
+                    denom: line.denom.clone(),
+                    required: change.checked_neg().ok_or_else(overflow)?,
+                    available,
+                });
+            }
+        }
+    }
+
+    // calculates the balance changes that must be applied to different accounts
+    // (negative means deduction, positive means addition)
+    let balances: Vec<Balance> = blance_changes
+        .into_iter()
+        .filter(|(_, coins)| !coins.is_empty())
+        .map(|(address, coins)| Balance::new(address, coins))
+        .collect();
+
+    // every denom of the inputs has an entry in burn_amounts, even if nothing is burnt.
+    let supply_changes: Vec<SupplyChange> = burn_amounts
+        .iter()
+        .map(|(denom, burned)| SupplyChange {
+            denom: denom.clone(),
+            burned: *burned,
+            commission: commission_amounts.get(denom).copied().unwrap_or(0),
+        })
+        .collect();
+
+    Ok(MultiSendReceipt {
+        fee_lines,
+        output_fee_lines,
+        commissions,
+        supply_changes,
+        balance_changes: balances,
+    })
+}
+
+// calculate_fee_lines validates the transaction and calculates the burn and commission every input must pay
+// on top of the transferred amount, one line per input and denom in the order of the inputs. It does not
+// look at any balance.
+pub fn calculate_fee_lines(
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+) -> Result<Vec<FeeLine>, MultiSendError> {
+    calculate_fee_lines_with_policy(registry, multi_send_tx, &ProportionalFeePolicy)
+}
+
+// calculate_fee_lines_with_policy works like `calculate_fee_lines`, with the fees decided by `policy`.
+pub fn calculate_fee_lines_with_policy(
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+    policy: &(impl FeePolicy + ?Sized),
+) -> Result<Vec<FeeLine>, MultiSendError> {
+    calculate_fees(registry, multi_send_tx, policy).map(|(fee_lines, _)| fee_lines)
+}
+
+// calculate_fees works like `calculate_fee_lines` with the fees decided by `policy`. It also returns a line
+// for every output that pays a fee, in the order of the outputs.
+fn calculate_fees(
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+    policy: &(impl FeePolicy + ?Sized),
+) -> Result<(Vec<FeeLine>, Vec<OutputFeeLine>), MultiSendError> {
+    multi_send_tx.validate()?;
+    validation::validate_denoms(registry, multi_send_tx)?;
+
+    //calculate sum of inputs and outputs in mulit_send_tx match.
+    let mut input_amounts = Coins::default();
+    let mut output_amounts = Coins::default();
+
+    for input in &multi_send_tx.inputs {
+        input_amounts = input_amounts.add(&input.coins)?;
+    }
+
+    for output in &multi_send_tx.outputs {
+        output_amounts = output_amounts.add(&output.coins)?;
+    }
+
+    //check that the input and output amounts match for each denom
+    for input_amount in &input_amounts {
+        let output_amount = output_amounts.amount_of(&input_amount.denom);
+        if input_amount.amount != output_amount {
+            return Err(MultiSendError::InputOutputMismatch {
+                denom: input_amount.denom.clone(),
+                input_sum: validated_sum(input_amount.amount),
+                output_sum: validated_sum(output_amount),
+            });
+        }
+    }
+
+    for output_amount in &output_amounts {
+        if input_amounts.amount_of(&output_amount.denom) == 0 {
+            return Err(MultiSendError::InputOutputMismatch {
+                denom: output_amount.denom.clone(),
+                input_sum: Amount::zero(),
+                output_sum: validated_sum(output_amount.amount),
+            });
+        }
+    }
+
+    //group the inputs and outputs by denom, in the order of the transaction
+    let mut transfers: BTreeMap<&Denom, DenomTransfers> = BTreeMap::new();
+    for (balances, is_input) in [
+        (&multi_send_tx.inputs, true),
+        (&multi_send_tx.outputs, false),
+    ] {
+        for balance in balances {
+            for coin in &balance.coins {
+                let definition =
+                    registry
+                        .get(&coin.denom)
+                        .ok_or_else(|| MultiSendError::UnknownDenoms {
+                            denoms: vec![coin.denom.clone()],
+                        })?;
+                let denom_transfers =
+                    transfers
+                        .entry(&coin.denom)
+                        .or_insert_with(|| DenomTransfers {
+                            definition,
+                            inputs: Vec::new(),
+                            outputs: Vec::new(),
+                        });
+                let legs = if is_input {
+                    &mut denom_transfers.inputs
+                } else {
+                    &mut denom_transfers.outputs
+                };
+                legs.push((&balance.address, coin.amount));
+            }
+        }
+    }
+
+    //let the policy calculate the fees of every denom
+    let mut fee_shares = BTreeMap::new();
+    for (denom, denom_transfers) in &transfers {
+        let shares = policy.fee_shares(denom_transfers)?;
+        let is_valid = shares.inputs.len() == denom_transfers.inputs.len()
+            && shares.outputs.len() == denom_transfers.outputs.len()
+            && shares
+                .inputs
+                .iter()
+                .chain(&shares.outputs)
+                .all(|share| share.burn >= 0 && share.commission >= 0);
+        if !is_valid {
+            return Err(MultiSendError::InvalidFeeShares {
+                denom: Denom::clone(denom),
+            });
+        }
+        fee_shares.insert(
+            *denom,
+            (shares.inputs.into_iter(), shares.outputs.into_iter()),
+        );
+    }
+
+    //every address appears once in the inputs and once in the outputs, so the shares of a denom are taken
+    //in the order of the transaction
+    let mut next_share = |denom: &Denom, is_input: bool| {
+        let (input_shares, output_shares) = fee_shares
+            .get_mut(denom)
+            .expect("every denom has fee shares");
+        let shares = if is_input {
+            input_shares
+        } else {
+            output_shares
+        };
+        shares.next().expect("a share for every input and output")
+    };
+
+    let mut fee_lines: Vec<FeeLine> = Vec::new();
+    for input in &multi_send_tx.inputs {
+        for coin in &input.coins {
+            let share = next_share(&coin.denom, true);
+            let mut total_amount: i128 = coin.amount;
+            add_amount(&mut total_amount, share.burn, &coin.denom)?;
+            add_amount(&mut total_amount, share.commission, &coin.denom)?;
+            fee_lines.push(FeeLine {
+                address: input.address.clone(),
+                denom: coin.denom.clone(),
+                transferred: coin.amount,
+                burn: share.burn,
+                commission: share.commission,
+                total_debited: total_amount,
+            });
+        }
+    }
+
+    let mut output_fee_lines: Vec<OutputFeeLine> = Vec::new();
+    for output in &multi_send_tx.outputs {
+        for coin in &output.coins {
+            let share = next_share(&coin.denom, false);
+            if share == FeeShare::default() {
+                continue;
+            }
+            let mut fee: i128 = share.burn;
+            add_amount(&mut fee, share.commission, &coin.denom)?;
+            if fee > coin.amount {
+                return Err(MultiSendError::InsufficientBalance {
+                    address: output.address.clone(),
+                    denom: coin.denom.clone(),
+                    required: fee,
+                    available: coin.amount,
+                });
+            }
+            output_fee_lines.push(OutputFeeLine {
+                address: output.address.clone(),
+                denom: coin.denom.clone(),
+                received: coin.amount,
+                burn: share.burn,
+                commission: share.commission,
+                total_credited: coin.amount - fee,
+            });
+        }
+    }
+
+    Ok((fee_lines, output_fee_lines))
+}
+
+// sum_balances adds up the coins of every address, so an address may be listed more than once.
+pub(crate) fn sum_balances(
+    balances: &[Balance],
+) -> Result<BTreeMap<Address, Coins>, MultiSendError> {
+    let mut amounts: BTreeMap<Address, Coins> = BTreeMap::new();
+    for balance in balances {
+        let coins = amounts.entry(balance.address.clone()).or_default();
+        *coins = coins.add(&balance.coins)?;
+    }
+    Ok(amounts)
+}
+
+// add_amount adds `amount` of `denom` to `total`. The transaction is rejected if the sum does not fit into
+// an i128, so hostile amounts can never wrap around.
+pub(crate) fn add_amount(
+    total: &mut i128,
+    amount: i128,
+    denom: &Denom,
+) -> Result<(), MultiSendError> {
+    *total = total
+        .checked_add(amount)
+        .ok_or_else(|| MultiSendError::Overflow {
+            denom: denom.clone(),
+        })?;
+    Ok(())
+}
+
+// validated_sum converts a sum of amounts of a validated transaction, which are all positive.
+pub(crate) fn validated_sum(sum: i128) -> Amount {
+    Amount::new(sum).expect("the amounts of a validated transaction are positive")
+}
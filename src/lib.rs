@@ -0,0 +1,53 @@
+// Calculates the balance changes of cosmos-like `MultiSend` transactions for denoms with burn and
+// commission fees. The modules are:
+// - `types`: the transaction, balance and denom definition types
+// - `address`, `denom`, `amount`: validated newtypes for the values the types are made of
+// - `coins`: the `Coins` set held by a balance, with its arithmetic
+// - `fees`: the calculation of fees and balance changes (`calculate_balance_changes`)
+// - `fee_policy`: the `FeePolicy` deciding the burn and commission, `ProportionalFeePolicy` by default
+// - `validation`: checks of transactions, with all problems reported at once if wanted
+// - `state`: a `Bank` that keeps balances and supplies across transactions, `block` executes many of them
+// The most used items are also exported at the root of the crate.
+
+pub mod address;
+pub mod amount;
+pub mod block;
+pub mod cli;
+pub mod coins;
+pub mod commission;
+pub mod decimal;
+pub mod denom;
+pub mod error;
+pub mod exemption;
+pub mod fee_limits;
+pub mod fee_policy;
+pub mod fees;
+pub mod rate_tiers;
+pub mod receipt;
+pub mod registry;
+mod serde_string;
+pub mod simulate;
+pub mod state;
+#[cfg(test)]
+mod test;
+pub mod types;
+pub mod validation;
+
+pub use address::Address;
+pub use amount::Amount;
+pub use coins::Coins;
+pub use decimal::Decimal;
+pub use denom::Denom;
+pub use error::MultiSendError;
+pub use fee_policy::{FeePolicy, ProportionalFeePolicy};
+pub use fees::{
+    calculate_balance_changes, calculate_balance_changes_reporting_all, calculate_balance_deltas,
+    calculate_multi_send_receipt, calculate_multi_send_receipt_with_funding,
+    calculate_multi_send_receipt_with_policy, FundingMode,
+};
+pub use receipt::MultiSendReceipt;
+pub use registry::DenomRegistry;
+pub use state::apply_deltas;
+pub use types::{
+    AccountBalance, Balance, BalanceDelta, Coin, DenomDefinition, MultiSend, TransferLeg,
+};
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::amount::Amount;
+use crate::decimal::Decimal;
+use crate::exemption::Fee;
+use crate::types::DenomDefinition;
+
+// RateTier replaces the flat rate of a fee once the amount the fee is charged on (`total_burn` in
+// `calculate_balance_changes`) reaches `from`. In JSON both fields are strings, e.g.
+// `{"from": "10000", "rate": "0.005"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateTier {
+    pub from: Amount,
+    pub rate: Decimal,
+}
+
+impl DenomDefinition {
+    // rate returns the rate of `fee` for a transaction in which the fee is charged on `base`: the rate of the
+    // last tier with `from <= base`, or the flat rate if there is no such tier. The tiers of a registered
+    // definition are sorted by `from`.
+    pub fn rate(&self, fee: Fee, base: i128) -> Decimal {
+        let (flat_rate, tiers) = match fee {
+            Fee::Burn => (self.burn_rate, &self.burn_tiers),
+            Fee::Commission => (self.commission_rate, &self.commission_tiers),
+        };
+        tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.from.get() <= base)
+            .map_or(flat_rate, |tier| tier.rate)
+    }
+
+    pub fn tiers(&self, fee: Fee) -> &[RateTier] {
+        match fee {
+            Fee::Burn => &self.burn_tiers,
+            Fee::Commission => &self.commission_tiers,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::address::Address;
+use crate::denom::Denom;
+use crate::types::Balance;
+
+// MultiSendReceipt is the detailed result of a `MultiSend` transaction. Next to the net balance changes
+// it explains how every input was debited and who received the commissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiSendReceipt {
+    // one line per input account and denom, in the order of the inputs and their coins
+    pub fee_lines: Vec<FeeLine>,
+    // one line per output account and denom that pays a fee out of the amount it receives, in the order of
+    // the outputs. Always empty with the default `ProportionalFeePolicy`, where only inputs pay.
+    pub output_fee_lines: Vec<OutputFeeLine>,
+    // the commission received by every issuer or beneficiary, sorted by denom
+    pub commissions: Vec<CommissionPayment>,
+    // the change in supply of every transferred denom, sorted by denom
+    pub supply_changes: Vec<SupplyChange>,
+    // the net balance changes (negative means deduction, positive means addition) sorted by address and
+    // denom, the same value `calculate_balance_changes` returns
+    pub balance_changes: Vec<Balance>,
+}
+
+impl MultiSendReceipt {
+    pub fn balance_changes(&self) -> &[Balance] {
+        &self.balance_changes
+    }
+
+    pub fn into_balance_changes(self) -> Vec<Balance> {
+        self.balance_changes
+    }
+}
+
+// FeeLine explains the debit of one input account for one denom, e.g. "1000 sent, 80 burnt,
+// 120 commission, 1200 debited".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeLine {
+    pub address: Address,
+    pub denom: Denom,
+    // the amount sent to the outputs
+    pub transferred: i128,
+    // the share of the burn paid by this input
+    pub burn: i128,
+    // the share of the commission paid by this input
+    pub commission: i128,
+    // transferred + burn + commission
+    pub total_debited: i128,
+}
+
+// OutputFeeLine explains the credit of one output account for one denom that pays a fee, e.g. "1000
+// received, 10 burnt, 0 commission, 990 credited".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputFeeLine {
+    pub address: Address,
+    pub denom: Denom,
+    // the amount sent to this output by the inputs
+    pub received: i128,
+    // the share of the burn paid by this output
+    pub burn: i128,
+    // the share of the commission paid by this output
+    pub commission: i128,
+    // received - burn - commission
+    pub total_credited: i128,
+}
+
+// CommissionPayment is the commission of one denom received by its issuer or by one of its commission
+// beneficiaries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommissionPayment {
+    pub address: Address,
+    pub denom: Denom,
+    pub amount: i128,
+}
+
+// SupplyChange is the effect of a transaction on the supply of one denom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupplyChange {
+    pub denom: Denom,
+    // the amount burnt from the senders, the total supply of the denom is reduced by this amount.
+    // The balance changes of the denom always sum up to -burned.
+    pub burned: i128,
+    // the amount paid to the issuer or commission beneficiaries as commission.
+    pub commission: i128,
+}
@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::address::Address;
+use crate::decimal::Decimal;
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::exemption::Fee;
+use crate::types::DenomDefinition;
+
+// DenomRegistry holds the validated definitions of all known denoms. It is built once from a list of
+// `DenomDefinition`s and then shared by every `calculate_balance_changes` call.
+pub struct DenomRegistry {
+    definitions: BTreeMap<Denom, DenomDefinition>,
+}
+
+impl DenomRegistry {
+    // new validates the definitions and rejects the whole set if a denom is defined twice,
+    // if a burn or commission rate (flat or tiered) is above 1, if the tiers of a fee are not strictly
+    // ascending from above zero, if the limits of a fee are invalid, or if a commission
+    // beneficiary is listed twice or has a weight of zero. Rates can not be negative or NaN, `Decimal` does not
+    // have such values.
+    pub fn new(definitions: Vec<DenomDefinition>) -> Result<Self, MultiSendError> {
+        let mut registry = DenomRegistry {
+            definitions: BTreeMap::new(),
+        };
+        for definition in definitions {
+            let tier_rates = Fee::ALL
+                .into_iter()
+                .flat_map(|fee| definition.tiers(fee))
+                .map(|tier| tier.rate);
+            let rates: Vec<Decimal> = [definition.burn_rate, definition.commission_rate]
+                .into_iter()
+                .chain(tier_rates)
+                .collect();
+            for rate in rates {
+                if rate > Decimal::one() {
+                    return Err(MultiSendError::InvalidRate {
+                        denom: definition.denom.clone(),
+                        rate,
+                    });
+                }
+            }
+            for fee in Fee::ALL {
+                let tiers = definition.tiers(fee);
+                let ascending = tiers.first().is_none_or(|tier| !tier.from.is_zero())
+                    && tiers.windows(2).all(|pair| pair[0].from < pair[1].from);
+                if !ascending {
+                    return Err(MultiSendError::InvalidRateTiers {
+                        denom: definition.denom.clone(),
+                        fee,
+                    });
+                }
+                if !definition.limits(fee).is_valid() {
+                    return Err(MultiSendError::InvalidFeeLimits {
+                        denom: definition.denom.clone(),
+                        fee,
+                    });
+                }
+            }
+            let mut beneficiaries = BTreeSet::new();
+            for beneficiary in &definition.commission_beneficiaries {
+                if beneficiary.weight == 0 {
+                    return Err(MultiSendError::ZeroBeneficiaryWeight {
+                        denom: definition.denom.clone(),
+                        address: beneficiary.address.clone(),
+                    });
+                }
+                if !beneficiaries.insert(&beneficiary.address) {
+                    return Err(MultiSendError::DuplicateBeneficiary {
+                        denom: definition.denom.clone(),
+                        address: beneficiary.address.clone(),
+                    });
+                }
+            }
+            if registry.contains(&definition.denom) {
+                return Err(MultiSendError::DuplicateDefinition {
+                    denom: definition.denom.clone(),
+                });
+            }
+            registry
+                .definitions
+                .insert(definition.denom.clone(), definition);
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, denom: &str) -> Option<&DenomDefinition> {
+        self.definitions.get(denom)
+    }
+
+    // definitions returns all definitions, sorted by denom.
+    pub fn definitions(&self) -> impl Iterator<Item = &DenomDefinition> {
+        self.definitions.values()
+    }
+
+    pub fn contains(&self, denom: &str) -> bool {
+        self.definitions.contains_key(denom)
+    }
+
+    pub fn issuer(&self, denom: &str) -> Option<&Address> {
+        self.get(denom).map(|definition| &definition.issuer)
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
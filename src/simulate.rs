@@ -0,0 +1,65 @@
+use crate::address::Address;
+use crate::coins::Coins;
+use crate::error::MultiSendError;
+use crate::fee_policy::{FeePolicy, ProportionalFeePolicy};
+use crate::fees::calculate_fee_lines_with_policy;
+use crate::receipt::FeeLine;
+use crate::registry::DenomRegistry;
+use crate::types::{Balance, Coin, MultiSend};
+
+// simulate_multi_send calculates what every input of `multi_send_tx` would be debited, without looking at
+// any balance. The fee lines are exactly the ones of the receipt if the transaction is executed: the
+// `total_debited` of a line is the amount the sender must hold, `burn + commission` is the fee on top of
+// the transferred amount. Errors are the ones of `calculate_balance_changes` that do not depend on
+// balances.
+pub fn simulate_multi_send(
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+) -> Result<Vec<FeeLine>, MultiSendError> {
+    simulate_multi_send_with_policy(registry, multi_send_tx, &ProportionalFeePolicy)
+}
+
+// simulate_multi_send_with_policy works like `simulate_multi_send`, with the fees decided by `policy`. Only
+// the inputs get a line, a fee the policy charges an output is not part of the simulation.
+pub fn simulate_multi_send_with_policy(
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+    policy: &dyn FeePolicy,
+) -> Result<Vec<FeeLine>, MultiSendError> {
+    calculate_fee_lines_with_policy(registry, multi_send_tx, policy)
+}
+
+// simulate_gross_input is the reverse of `simulate_multi_send` for a single transfer: `sender` wants
+// `recipient` to receive exactly `net`. The fees are charged on top of the transferred amount, so the
+// recipient receives the transferred amount unchanged and the returned line tells how much the sender is
+// debited in total (`total_debited`).
+pub fn simulate_gross_input(
+    registry: &DenomRegistry,
+    sender: Address,
+    recipient: Address,
+    net: Coin,
+) -> Result<FeeLine, MultiSendError> {
+    simulate_gross_input_with_policy(registry, sender, recipient, net, &ProportionalFeePolicy)
+}
+
+// simulate_gross_input_with_policy works like `simulate_gross_input`, with the fees decided by `policy`.
+pub fn simulate_gross_input_with_policy(
+    registry: &DenomRegistry,
+    sender: Address,
+    recipient: Address,
+    net: Coin,
+    policy: &dyn FeePolicy,
+) -> Result<FeeLine, MultiSendError> {
+    let multi_send_tx = MultiSend {
+        inputs: vec![Balance {
+            address: sender,
+            coins: Coins::from(net.clone()),
+        }],
+        outputs: vec![Balance {
+            address: recipient,
+            coins: Coins::from(net),
+        }],
+    };
+    let mut fee_lines = calculate_fee_lines_with_policy(registry, &multi_send_tx, policy)?;
+    Ok(fee_lines.remove(0))
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use crate::address::Address;
+use crate::amount::Amount;
+use crate::coins::Coins;
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::fee_policy::{FeePolicy, ProportionalFeePolicy};
+use crate::fees::{add_amount, calculate_multi_send_receipt_with_policy, FundingMode};
+use crate::receipt::MultiSendReceipt;
+use crate::registry::DenomRegistry;
+use crate::types::{AccountBalance, Balance, BalanceDelta, MultiSend};
+
+// Supply is the supply ledger of one denom.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Supply {
+    // the amount held by all accounts together
+    pub total: i128,
+    // the amount burnt by all executed transactions
+    pub burned: i128,
+    // the amount paid as commission by all executed transactions
+    pub commission: i128,
+}
+
+// Bank is an in-memory keeper (like the bank module of cosmos sdk). It owns the account balances and the
+// denom definitions and applies `MultiSend` transactions to the balances.
+pub struct Bank {
+    registry: DenomRegistry,
+    // decides the burn and commission of every executed transaction
+    policy: Box<dyn FeePolicy>,
+    // address -> coins, accounts never hold negative amounts and accounts without coins are removed
+    balances: BTreeMap<Address, Coins>,
+    // denom -> supply ledger
+    supplies: BTreeMap<Denom, Supply>,
+}
+
+impl Bank {
+    // new creates a bank from the genesis balances. Several entries for the same address are merged.
+    // Negative amounts and total supplies that do not fit into an i128 are rejected. The fees are decided by
+    // `ProportionalFeePolicy`.
+    pub fn new(registry: DenomRegistry, balances: Vec<Balance>) -> Result<Self, MultiSendError> {
+        Bank::with_policy(registry, balances, Box::new(ProportionalFeePolicy))
+    }
+
+    // with_policy works like `new`, with the fees of every executed transaction decided by `policy`.
+    pub fn with_policy(
+        registry: DenomRegistry,
+        balances: Vec<Balance>,
+        policy: Box<dyn FeePolicy>,
+    ) -> Result<Self, MultiSendError> {
+        let mut bank = Bank {
+            registry,
+            policy,
+            balances: BTreeMap::new(),
+            supplies: BTreeMap::new(),
+        };
+        for balance in balances {
+            let account = bank.balances.entry(balance.address.clone()).or_default();
+            for coin in balance.coins {
+                if coin.amount < 0 {
+                    return Err(MultiSendError::NegativeBalance {
+                        address: balance.address.clone(),
+                        denom: coin.denom.clone(),
+                        amount: coin.amount,
+                    });
+                }
+                add_amount(
+                    &mut bank.supplies.entry(coin.denom.clone()).or_default().total,
+                    coin.amount,
+                    &coin.denom,
+                )?;
+                account.add_coin(coin)?;
+            }
+        }
+        bank.balances.retain(|_, account| !account.is_empty());
+        Ok(bank)
+    }
+
+    pub fn registry(&self) -> &DenomRegistry {
+        &self.registry
+    }
+
+    // execute_multi_send calculates the balance changes of `multi_send_tx` and applies them to the balances
+    // and the supply ledger. Either all changes are applied or, if the transaction is rejected, none of them.
+    pub fn execute_multi_send(
+        &mut self,
+        multi_send_tx: MultiSend,
+    ) -> Result<MultiSendReceipt, MultiSendError> {
+        let original_balances = multi_send_tx
+            .inputs
+            .iter()
+            .map(|input| Balance {
+                address: input.address.clone(),
+                coins: self.all_balances(&input.address),
+            })
+            .collect();
+        let receipt = calculate_multi_send_receipt_with_policy(
+            original_balances,
+            &self.registry,
+            multi_send_tx,
+            FundingMode::default(),
+            self.policy.as_ref(),
+        )?;
+
+        // calculate every new amount before touching the state, so an error leaves it unchanged.
+        // calculate_balance_changes already checked that no account is debited more than it holds.
+        let mut updates: Vec<(&Address, Coins)> = Vec::new();
+        for change in &receipt.balance_changes {
+            let account = self.all_balances(&change.address).add(&change.coins)?;
+            updates.push((&change.address, account));
+        }
+        let mut supply_updates: Vec<(&Denom, Supply)> = Vec::new();
+        for change in &receipt.supply_changes {
+            let mut supply = self.supply(&change.denom);
+            add_amount(&mut supply.total, -change.burned, &change.denom)?;
+            add_amount(&mut supply.burned, change.burned, &change.denom)?;
+            add_amount(&mut supply.commission, change.commission, &change.denom)?;
+            supply_updates.push((&change.denom, supply));
+        }
+
+        for (address, account) in updates {
+            if account.is_empty() {
+                self.balances.remove(address);
+            } else {
+                self.balances.insert(address.clone(), account);
+            }
+        }
+        for (denom, supply) in supply_updates {
+            self.supplies.insert(denom.clone(), supply);
+        }
+        Ok(receipt)
+    }
+
+    // balance returns the amount of `denom` held by `address`, zero if it holds none.
+    pub fn balance(&self, address: &str, denom: &str) -> Amount {
+        self.balances
+            .get(address)
+            .and_then(|account| Amount::new(account.amount_of(denom)))
+            .unwrap_or_default()
+    }
+
+    // all_balances returns every coin held by `address`, sorted by denom.
+    pub fn all_balances(&self, address: &str) -> Coins {
+        self.balances.get(address).cloned().unwrap_or_default()
+    }
+
+    // accounts returns the balances of every account holding coins, sorted by address and denom.
+    pub fn accounts(&self) -> Vec<Balance> {
+        self.balances
+            .keys()
+            .map(|address| Balance {
+                address: address.clone(),
+                coins: self.all_balances(address),
+            })
+            .collect()
+    }
+
+    // supply returns the supply ledger of `denom`.
+    pub fn supply(&self, denom: &str) -> Supply {
+        self.supplies.get(denom).copied().unwrap_or_default()
+    }
+
+    // total_supply returns the sum of `denom` over all accounts. It always fits into an i128: the genesis
+    // supply is checked in `new` and transactions can only burn tokens, never mint them.
+    pub fn total_supply(&self, denom: &str) -> Amount {
+        Amount::new(self.supply(denom).total).unwrap_or_default()
+    }
+}
+
+// apply_deltas applies `deltas` to `balances` and returns the new balances, sorted by address. Entries for
+// the same address are added up, zero amounts and accounts without coins are left out. If an account would
+// end up with a negative amount, nothing is applied and the underflow is reported as `InsufficientBalance`
+// with the net debit of the deltas and the amount held before.
+pub fn apply_deltas(
+    balances: Vec<AccountBalance>,
+    deltas: &[BalanceDelta],
+) -> Result<Vec<AccountBalance>, MultiSendError> {
+    let mut amounts: BTreeMap<Address, Coins> = BTreeMap::new();
+    for balance in &balances {
+        let account = amounts.entry(balance.address().clone()).or_default();
+        *account = account.add(&balance.to_coins())?;
+    }
+    let original_amounts = amounts.clone();
+
+    for delta in deltas {
+        let account = amounts.entry(delta.address.clone()).or_default();
+        *account = account.add(&delta.coins)?;
+    }
+
+    let mut new_balances: Vec<AccountBalance> = Vec::new();
+    for (address, account) in amounts {
+        for coin in &account {
+            if coin.amount < 0 {
+                let available = original_amounts
+                    .get(&address)
+                    .map_or(0, |account| account.amount_of(&coin.denom));
+                return Err(MultiSendError::InsufficientBalance {
+                    required: available.checked_sub(coin.amount).ok_or_else(|| {
+                        MultiSendError::Overflow {
+                            denom: coin.denom.clone(),
+                        }
+                    })?,
+                    address: address.clone(),
+                    denom: coin.denom.clone(),
+                    available,
+                });
+            }
+        }
+        if !account.is_empty() {
+            new_balances.push(AccountBalance::try_from(Balance::new(address, account))?);
+        }
+    }
+    Ok(new_balances)
+}
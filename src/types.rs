@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::amount::Amount;
+use crate::coins::Coins;
+use crate::commission::CommissionBeneficiary;
+use crate::decimal::Decimal;
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::exemption::FeeExemption;
+use crate::fee_limits::FeeLimits;
+use crate::rate_tiers::RateTier;
+use crate::serde_string;
+use crate::validation;
+
+// A user can submit a `MultiSend` transaction (similar to bank.MultiSend in cosmos sdk) to transfer multiple
+// coins (denoms) from multiple input addresses to multiple output addresses. A denom is the name or symbol
+// for a coin type, e.g USDT and USDC can be considered different denoms; in cosmos ecosystem they are called
+// denoms, in ethereum world they are called symbols.
+// The sum of input coins and output coins must match for every transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MultiSend {
+    // inputs contain the list of accounts that want to send coins from, and how many coins from each account we want to send.
+    pub inputs: Vec<Balance>,
+    // outputs contains the list of accounts that we want to deposit coins into, and how many coins to deposit into
+    // each account
+    pub outputs: Vec<Balance>,
+}
+
+impl MultiSend {
+    pub fn new(inputs: Vec<Balance>, outputs: Vec<Balance>) -> Self {
+        MultiSend { inputs, outputs }
+    }
+
+    // from_legs creates a transaction from inputs and outputs whose amounts are already known to be positive.
+    pub fn from_legs(inputs: Vec<TransferLeg>, outputs: Vec<TransferLeg>) -> Self {
+        MultiSend {
+            inputs: inputs.into_iter().map(Balance::from).collect(),
+            outputs: outputs.into_iter().map(Balance::from).collect(),
+        }
+    }
+}
+
+// In JSON the amount is a string, e.g. `{"denom": "denom1", "amount": "1000"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Coin {
+    pub denom: Denom,
+    #[serde(with = "serde_string")]
+    pub amount: i128,
+}
+
+impl Coin {
+    pub fn new(denom: Denom, amount: i128) -> Self {
+        Coin { denom, amount }
+    }
+}
+
+// A Balance is the coins of an address in untyped form: an input or output of a transaction, an original
+// balance or a balance change. `Coins` already rejects repeated denoms and zero amounts, `MultiSend::validate`
+// also requires the amounts of inputs and outputs to be positive. The typed `AccountBalance` and
+// `TransferLeg` convert from a Balance and back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Balance {
+    pub address: Address,
+    pub coins: Coins,
+}
+
+impl Balance {
+    pub fn new(address: Address, coins: Coins) -> Self {
+        Balance { address, coins }
+    }
+}
+
+// AccountBalance is what an account holds: a non-negative `Amount` per denom, zero amounts are left out. In
+// JSON it has the same form as a `Balance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Balance", into = "Balance")]
+pub struct AccountBalance {
+    address: Address,
+    coins: BTreeMap<Denom, Amount>,
+}
+
+impl AccountBalance {
+    pub fn new(address: Address, mut coins: BTreeMap<Denom, Amount>) -> Self {
+        coins.retain(|_, amount| !amount.is_zero());
+        AccountBalance { address, coins }
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn coins(&self) -> &BTreeMap<Denom, Amount> {
+        &self.coins
+    }
+
+    // amount_of returns the amount of `denom`, zero if the account does not hold it.
+    pub fn amount_of(&self, denom: &str) -> Amount {
+        self.coins.get(denom).copied().unwrap_or_default()
+    }
+
+    // to_coins returns the holding as `Coins`, e.g. to add balance changes to it.
+    pub fn to_coins(&self) -> Coins {
+        to_coins(&self.coins)
+    }
+}
+
+// negative amounts are rejected.
+impl TryFrom<Balance> for AccountBalance {
+    type Error = MultiSendError;
+
+    fn try_from(balance: Balance) -> Result<Self, Self::Error> {
+        if let Some(coin) = balance.coins.iter().find(|coin| coin.amount < 0) {
+            return Err(MultiSendError::NegativeBalance {
+                address: balance.address.clone(),
+                denom: coin.denom.clone(),
+                amount: coin.amount,
+            });
+        }
+        let coins = balance
+            .coins
+            .into_iter()
+            .map(|coin| (coin.denom, Amount::new(coin.amount).expect("checked above")))
+            .collect();
+        Ok(AccountBalance::new(balance.address, coins))
+    }
+}
+
+impl From<AccountBalance> for Balance {
+    fn from(balance: AccountBalance) -> Self {
+        Balance {
+            coins: balance.to_coins(),
+            address: balance.address,
+        }
+    }
+}
+
+// TransferLeg is one input or output of a transaction: an address and the positive `Amount` of every denom
+// it sends or receives. In JSON it has the same form as a `Balance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Balance", into = "Balance")]
+pub struct TransferLeg {
+    address: Address,
+    coins: BTreeMap<Denom, Amount>,
+}
+
+impl TransferLeg {
+    // new rejects empty coins and zero amounts, like `MultiSend::validate` does for every input and output.
+    pub fn new(address: Address, coins: BTreeMap<Denom, Amount>) -> Result<Self, MultiSendError> {
+        if let Some((denom, _)) = coins.iter().find(|(_, amount)| amount.is_zero()) {
+            return Err(MultiSendError::NonPositiveAmount {
+                address: address.clone(),
+                denom: denom.clone(),
+                amount: 0,
+            });
+        }
+        TransferLeg::try_from(Balance::new(address, to_coins(&coins)))
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn coins(&self) -> &BTreeMap<Denom, Amount> {
+        &self.coins
+    }
+
+    // amount_of returns the amount of `denom`, zero if the leg does not transfer it.
+    pub fn amount_of(&self, denom: &str) -> Amount {
+        self.coins.get(denom).copied().unwrap_or_default()
+    }
+
+    pub fn to_coins(&self) -> Coins {
+        to_coins(&self.coins)
+    }
+}
+
+// empty coins and negative amounts are rejected.
+impl TryFrom<Balance> for TransferLeg {
+    type Error = MultiSendError;
+
+    fn try_from(balance: Balance) -> Result<Self, Self::Error> {
+        validation::validate_coins(&balance)?;
+        let coins = balance
+            .coins
+            .into_iter()
+            .map(|coin| Ok((coin.denom, Amount::try_from(coin.amount)?)))
+            .collect::<Result<_, MultiSendError>>()?;
+        Ok(TransferLeg {
+            address: balance.address,
+            coins,
+        })
+    }
+}
+
+impl From<TransferLeg> for Balance {
+    fn from(leg: TransferLeg) -> Self {
+        Balance {
+            coins: leg.to_coins(),
+            address: leg.address,
+        }
+    }
+}
+
+// to_coins converts amounts keyed by denom to `Coins`, zero amounts are left out.
+fn to_coins(amounts: &BTreeMap<Denom, Amount>) -> Coins {
+    let coins = amounts
+        .iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(denom, amount)| Coin::new(denom.clone(), amount.get()))
+        .collect();
+    Coins::new(coins).expect("the denoms of a map are distinct")
+}
+
+// BalanceDelta is a signed change of the balance of an account (negative means deduction, positive means
+// addition), as calculated for a transaction. In JSON it has the same form as a `Balance`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BalanceDelta {
+    pub address: Address,
+    pub coins: Coins,
+}
+
+impl BalanceDelta {
+    pub fn new(address: Address, coins: Coins) -> Self {
+        BalanceDelta { address, coins }
+    }
+}
+
+impl From<Balance> for BalanceDelta {
+    fn from(balance: Balance) -> Self {
+        BalanceDelta {
+            address: balance.address,
+            coins: balance.coins,
+        }
+    }
+}
+
+impl From<BalanceDelta> for Balance {
+    fn from(delta: BalanceDelta) -> Self {
+        Balance {
+            address: delta.address,
+            coins: delta.coins,
+        }
+    }
+}
+
+// A Denom has a definition (`CoinDefinition`) which contains different attributes related to the denom.
+// Definitions are registered in a `DenomRegistry`, which validates them. In JSON the rates are decimal
+// strings, e.g. `"burn_rate": "0.08"`:
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DenomDefinition {
+    // the unique identifier for the token (e.g `core`, `eth`, `usdt`, etc.)
+    pub denom: Denom,
+    // The address that created the token
+    pub issuer: Address,
+    // burn_rate is a number between 0 and 1. If it is above zero, in every transfer,
+    // some additional tokens will be burnt on top of the transferred value, from the senders address.
+    // The tokens to be burnt are calculated by multiplying the TransferAmount by burn rate, and
+    // rounding it up to an integer value. For example if an account sends 100 token and burn_rate is
+    // 0.2, then 120 (100 + 100 * 0.2) will be deducted from sender account and 100 will be deposited to the recipient
+    // account (i.e 20 tokens will be burnt)
+    pub burn_rate: Decimal,
+    // commission_rate is exactly same as the burn_rate, but the calculated value will be transferred to the
+    // issuer's account address instead of being burnt.
+    pub commission_rate: Decimal,
+    // if not empty, the commission is split between these addresses by weight instead of going to the issuer
+    // (see `DenomDefinition::split_commission`). Optional in JSON.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commission_beneficiaries: Vec<CommissionBeneficiary>,
+    // rates that replace burn_rate and commission_rate from a minimum `total_burn` on, sorted by that
+    // minimum (see `DenomDefinition::rate`). Optional in JSON.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub burn_tiers: Vec<RateTier>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commission_tiers: Vec<RateTier>,
+    // bounds of the burn and of the commission a transaction pays for this denom. Optional in JSON.
+    #[serde(default, skip_serializing_if = "FeeLimits::is_empty")]
+    pub burn_limits: FeeLimits,
+    #[serde(default, skip_serializing_if = "FeeLimits::is_empty")]
+    pub commission_limits: FeeLimits,
+    // addresses that are exempt from the fees like the issuer, possibly only from one fee or only in one
+    // direction. Optional in JSON.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exemptions: Vec<FeeExemption>,
+}
+
+impl DenomDefinition {
+    // new creates a definition with flat rates and without any of the optional attributes, which can be set
+    // on the returned value.
+    pub fn new(
+        denom: Denom,
+        issuer: Address,
+        burn_rate: Decimal,
+        commission_rate: Decimal,
+    ) -> Self {
+        DenomDefinition {
+            denom,
+            issuer,
+            burn_rate,
+            commission_rate,
+            commission_beneficiaries: Vec::new(),
+            burn_tiers: Vec::new(),
+            commission_tiers: Vec::new(),
+            burn_limits: FeeLimits::default(),
+            commission_limits: FeeLimits::default(),
+            exemptions: Vec::new(),
+        }
+    }
+}
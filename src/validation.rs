@@ -0,0 +1,252 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::denom::Denom;
+use crate::error::MultiSendError;
+use crate::fee_policy::{FeePolicy, ProportionalFeePolicy};
+use crate::fees::{calculate_fee_lines_with_policy, sum_balances, validated_sum};
+use crate::registry::DenomRegistry;
+use crate::types::{Balance, MultiSend};
+
+// The longest denom accepted by `is_valid_denom`, same as in cosmos sdk.
+const MAX_DENOM_LENGTH: usize = 128;
+// The length of the hex-encoded SHA-256 hash in an ibc denom.
+const IBC_HASH_LENGTH: usize = 64;
+
+impl MultiSend {
+    // validate performs the stateless checks of a transaction (like `ValidateBasic` in cosmos sdk), which
+    // do not need balances or denom definitions:
+    // - there is at least one input and one output
+    // - no address appears twice in the inputs or twice in the outputs
+    // - every input and output has coins, and every amount is positive (`Coins` already rejects zero amounts
+    //   and denoms that appear twice)
+    pub fn validate(&self) -> Result<(), MultiSendError> {
+        if self.inputs.is_empty() {
+            return Err(MultiSendError::EmptyInputs);
+        }
+        if self.outputs.is_empty() {
+            return Err(MultiSendError::EmptyOutputs);
+        }
+
+        let mut input_addresses = BTreeSet::new();
+        for input in &self.inputs {
+            if !input_addresses.insert(&input.address) {
+                return Err(MultiSendError::DuplicateInputAddress {
+                    address: input.address.clone(),
+                });
+            }
+            validate_coins(input)?;
+        }
+
+        let mut output_addresses = BTreeSet::new();
+        for output in &self.outputs {
+            if !output_addresses.insert(&output.address) {
+                return Err(MultiSendError::DuplicateOutputAddress {
+                    address: output.address.clone(),
+                });
+            }
+            validate_coins(output)?;
+        }
+
+        Ok(())
+    }
+}
+
+// validate_coins checks the coins of a single input or output.
+pub(crate) fn validate_coins(balance: &Balance) -> Result<(), MultiSendError> {
+    if balance.coins.is_empty() {
+        return Err(MultiSendError::EmptyCoins {
+            address: balance.address.clone(),
+        });
+    }
+    for coin in &balance.coins {
+        if coin.amount <= 0 {
+            return Err(MultiSendError::NonPositiveAmount {
+                address: balance.address.clone(),
+                denom: coin.denom.clone(),
+                amount: coin.amount,
+            });
+        }
+    }
+    Ok(())
+}
+
+// is_valid_denom reports whether `denom` matches the cosmos sdk denom format `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`.
+// Denoms of the reserved "ibc" and "factory" namespaces must also be well-formed: `ibc/<hash>` with a
+// SHA-256 hash in hex, and `factory/<creator>/<subdenom>` with a non-empty creator and subdenom.
+pub fn is_valid_denom(denom: &str) -> bool {
+    let mut chars = denom.chars();
+    let starts_with_letter = chars.next().is_some_and(|c| c.is_ascii_alphabetic());
+    let matches_format = starts_with_letter
+        && (3..=MAX_DENOM_LENGTH).contains(&denom.len())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !matches_format {
+        return false;
+    }
+    let parts: Vec<&str> = denom.split('/').collect();
+    match parts[0] {
+        "ibc" => {
+            parts.len() == 2
+                && parts[1].len() == IBC_HASH_LENGTH
+                && parts[1].bytes().all(|byte| byte.is_ascii_hexdigit())
+        }
+        "factory" => parts.len() == 3 && parts.iter().all(|part| !part.is_empty()),
+        _ => true,
+    }
+}
+
+// validate_denoms rejects the transaction if any input or output coin uses a denom without a
+// `DenomDefinition`. Every unknown denom is reported once, in the order it first appears in the
+// inputs and then in the outputs.
+pub fn validate_denoms(
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+) -> Result<(), MultiSendError> {
+    let mut unknown_denoms: Vec<Denom> = Vec::new();
+    for balance in multi_send_tx.inputs.iter().chain(&multi_send_tx.outputs) {
+        for coin in &balance.coins {
+            if !registry.contains(&coin.denom) && !unknown_denoms.contains(&coin.denom) {
+                unknown_denoms.push(coin.denom.clone());
+            }
+        }
+    }
+
+    if unknown_denoms.is_empty() {
+        Ok(())
+    } else {
+        Err(MultiSendError::UnknownDenoms {
+            denoms: unknown_denoms,
+        })
+    }
+}
+
+// collect_errors is the exhaustive counterpart of the checks done by `calculate_balance_changes`, which
+// stops at the first problem. It returns every problem of the transaction, or nothing if it can be
+// executed:
+// - a malformed transaction (see `MultiSend::validate`) is reported alone, it can not be checked further
+// - all unknown denoms, in one `UnknownDenoms` error
+// - every denom whose inputs and outputs do not match (or do not fit into an i128), sorted by denom
+// - every input without an original balance, and every input that can not pay a fee line, in the order
+//   of the inputs. Fees are calculated per denom, so they are still checked for the denoms without any
+//   of the problems above. Balances are checked like `FundingMode::OriginalBalance`.
+pub fn collect_errors(
+    original_balances: &[Balance],
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+) -> Vec<MultiSendError> {
+    collect_errors_with_policy(
+        original_balances,
+        registry,
+        multi_send_tx,
+        &ProportionalFeePolicy,
+    )
+}
+
+// collect_errors_with_policy works like `collect_errors`, with the fee lines calculated by `policy`.
+pub fn collect_errors_with_policy(
+    original_balances: &[Balance],
+    registry: &DenomRegistry,
+    multi_send_tx: &MultiSend,
+    policy: &dyn FeePolicy,
+) -> Vec<MultiSendError> {
+    if let Err(err) = multi_send_tx.validate() {
+        return vec![err];
+    }
+
+    let mut errors: Vec<MultiSendError> = Vec::new();
+    let mut rejected_denoms: BTreeSet<Denom> = BTreeSet::new();
+    if let Err(err) = validate_denoms(registry, multi_send_tx) {
+        if let MultiSendError::UnknownDenoms { denoms } = &err {
+            rejected_denoms.extend(denoms.iter().cloned());
+        }
+        errors.push(err);
+    }
+
+    // the sums of every denom, None if the sum does not fit into an i128
+    let sum = |balances: &[Balance]| {
+        let mut sums: BTreeMap<Denom, Option<i128>> = BTreeMap::new();
+        for coin in balances.iter().flat_map(|balance| &balance.coins) {
+            let total = sums.entry(coin.denom.clone()).or_insert(Some(0));
+            *total = total.and_then(|total| total.checked_add(coin.amount));
+        }
+        sums
+    };
+    let input_sums = sum(&multi_send_tx.inputs);
+    let output_sums = sum(&multi_send_tx.outputs);
+    let denoms: BTreeSet<&Denom> = input_sums.keys().chain(output_sums.keys()).collect();
+    for denom in denoms {
+        let input_sum = input_sums.get(denom).copied().unwrap_or(Some(0));
+        let output_sum = output_sums.get(denom).copied().unwrap_or(Some(0));
+        match (input_sum, output_sum) {
+            (Some(input_sum), Some(output_sum)) if input_sum == output_sum => continue,
+            (Some(input_sum), Some(output_sum)) => {
+                errors.push(MultiSendError::InputOutputMismatch {
+                    denom: denom.clone(),
+                    input_sum: validated_sum(input_sum),
+                    output_sum: validated_sum(output_sum),
+                });
+            }
+            _ => errors.push(MultiSendError::Overflow {
+                denom: denom.clone(),
+            }),
+        }
+        rejected_denoms.insert(denom.clone());
+    }
+
+    let original_amounts = match sum_balances(original_balances) {
+        Ok(original_amounts) => original_amounts,
+        Err(err) => {
+            errors.push(err);
+            return errors;
+        }
+    };
+    for input in &multi_send_tx.inputs {
+        if !original_amounts.contains_key(&input.address) {
+            errors.push(MultiSendError::MissingOriginalBalance {
+                address: input.address.clone(),
+            });
+        }
+    }
+
+    // the fees of the remaining denoms do not depend on the rejected ones.
+    let without_rejected = |balances: &[Balance]| -> Vec<Balance> {
+        balances
+            .iter()
+            .map(|balance| {
+                let mut coins = balance.coins.clone();
+                coins.retain(|coin| !rejected_denoms.contains(&coin.denom));
+                Balance::new(balance.address.clone(), coins)
+            })
+            .filter(|balance| !balance.coins.is_empty())
+            .collect()
+    };
+    let checkable = MultiSend {
+        inputs: without_rejected(&multi_send_tx.inputs),
+        outputs: without_rejected(&multi_send_tx.outputs),
+    };
+    if checkable.inputs.is_empty() {
+        return errors;
+    }
+    let fee_lines = match calculate_fee_lines_with_policy(registry, &checkable, policy) {
+        Ok(fee_lines) => fee_lines,
+        Err(err) => {
+            errors.push(err);
+            return errors;
+        }
+    };
+    for line in fee_lines {
+        let Some(coins) = original_amounts.get(&line.address) else {
+            continue;
+        };
+        let available = coins.amount_of(&line.denom);
+        if available < line.total_debited {
+            errors.push(MultiSendError::InsufficientBalance {
+                address: line.address.clone(),
+                denom: line.denom.clone(),
+                required: line.total_debited,
+                available,
+            });
+        }
+    }
+
+    errors
+}